@@ -0,0 +1,33 @@
+//Prints the empirical order of convergence of every solver preset on the reference problems in simulation::convergence.
+//Run with: cargo run --example solver_convergence, adding --features f64 to check the double precision build.
+//The same measurements are asserted by tests/convergence.rs
+use bevy_flatland::simulation::{
+    DiffEqSolverConfig, SymplecticSolverConfig, 
+    convergence::{convergence_table, convergence_step_counts, measure_symplectic_convergence, ReferenceProblem, ConvergenceResult}
+};
+
+fn main() {
+    println!("{:<20} {:<20} {:>10} {:>8}  errors", "method", "problem", "conditions", "observed");
+    for result in convergence_table(){
+        let conditions = DiffEqSolverConfig::preset(&result.method).map(|config| config.accuracy_order()).unwrap_or(0);
+        print_result(&result, &conditions.to_string());
+    }
+    let symplectic = [
+        ("semi_implicit_euler", SymplecticSolverConfig::semi_implicit_euler()),
+        ("velocity_verlet", SymplecticSolverConfig::velocity_verlet()),
+        ("leapfrog", SymplecticSolverConfig::leapfrog()),
+        ("forest_ruth", SymplecticSolverConfig::forest_ruth())
+    ];
+    for (name, config) in symplectic.iter(){
+        for problem in ReferenceProblem::ALL.into_iter().filter(ReferenceProblem::is_conservative){
+            let result = measure_symplectic_convergence(name, config, problem, &convergence_step_counts(config.order()));
+            print_result(&result, &config.order().to_string());
+        }
+    }
+}
+
+fn print_result(result: &ConvergenceResult, expected: &str){
+    let observed = result.observed_order.map(|order| format!("{order:.2}")).unwrap_or("-".to_string());
+    let errors: Vec<String> = result.errors.iter().map(|error| format!("{error:.2e}")).collect();
+    println!("{:<20} {:<20} {:>10} {:>8}  {}", result.method, result.problem.name(), expected, observed, errors.join(" "));
+}
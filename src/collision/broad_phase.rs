@@ -0,0 +1,274 @@
+use bevy::{diagnostic::{DiagnosticId, Diagnostics}, ecs::query::Has};
+use super::*;
+
+//Axis aligned bounding box in world space
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Aabb2D{
+    pub min: Vector,
+    pub max: Vector
+}
+impl Aabb2D{
+    pub fn new(min: Vector, max: Vector) -> Self{
+        Self{min, max}
+    }
+    pub fn union(&self, other: &Aabb2D) -> Self{
+        Self{min: self.min.min(other.min), max: self.max.max(other.max)}
+    }
+    pub fn expanded(&self, margin: Scalar) -> Self{
+        Self{min: self.min - Vector::splat(margin), max: self.max + Vector::splat(margin)}
+    }
+    //touching boxes count as overlapping
+    pub fn overlaps(&self, other: &Aabb2D) -> bool{
+        self.min.x <= other.max.x && other.min.x <= self.max.x && self.min.y <= other.max.y && other.min.y <= self.max.y
+    }
+    pub fn contains_point(&self, point: Vector) -> bool{
+        point.cmpge(self.min).all() && point.cmple(self.max).all()
+    }
+    //0 for points inside
+    pub fn distance_to_point(&self, point: Vector) -> Scalar{
+        (self.min - point).max(point - self.max).max(Vector::ZERO).length()
+    }
+    //Distance along the ray to where it enters the box, 0 if it starts inside. <direction> has unit length
+    pub fn ray_distance(&self, origin: Vector, direction: Vector, max_distance: Scalar) -> Option<Scalar>{
+        let (mut enter, mut exit): (Scalar, Scalar) = (0.0, max_distance);
+        for axis in 0..2{
+            if direction[axis].abs() <= Scalar::EPSILON {
+                if origin[axis] < self.min[axis] || origin[axis] > self.max[axis] {
+                    return None;
+                }
+                continue;
+            }
+            let (near, far) = ((self.min[axis] - origin[axis])/direction[axis], (self.max[axis] - origin[axis])/direction[axis]);
+            enter = enter.max(near.min(far));
+            exit = exit.min(near.max(far));
+        }
+        (enter <= exit).then_some(enter)
+    }
+}
+
+impl ConvexShape2D{
+    pub fn aabb(&self) -> Aabb2D{
+        let (min, max) = self.vertices.iter().fold((Vector::splat(Scalar::MAX), Vector::splat(Scalar::MIN)), |(min, max), vertex| {
+            (min.min(*vertex), max.max(*vertex))
+        });
+        Aabb2D::new(min, max).expanded(self.radius)
+    }
+}
+
+//Bounds of every convex piece of <collider> placed at <pose>.
+//A polyline with less than two vertices has no pieces and is bounded by its origin
+pub fn collider_aabb(collider: Collider2D, pose: Isometry2D) -> Aabb2D{
+    convex_parts(collider, pose).iter()
+        .map(|part| part.aabb())
+        .reduce(|bounds, aabb| bounds.union(&aabb))
+        .unwrap_or(Aabb2D::new(pose.translation, pose.translation))
+}
+
+//A collider as seen by the broad phase, placed at the state of the latest simulation step
+#[derive(Clone, Copy, Debug)]
+pub struct ColliderProxy2D{
+    pub entity: Entity,
+    //None for static colliders
+    pub body: Option<Entity>,
+    //Static for static colliders
+    pub body_type: RigidBodyType2D,
+    pub pose: Isometry2D,
+    pub aabb: Aabb2D,
+    //polylines are static terrain and never tested against each other
+    pub polyline: bool,
+    pub layers: CollisionLayers2D,
+    pub sensor: bool,
+    //on a Sleeping body, which hasn't moved since it fell asleep
+    pub sleeping: bool
+}
+impl ColliderProxy2D{
+    //Static and sleeping colliders hold still between steps
+    pub fn is_resting(&self) -> bool{
+        self.body.is_none() || self.sleeping
+    }
+    pub fn is_dynamic(&self) -> bool{
+        self.body_type == RigidBodyType2D::Dynamic
+    }
+    //Pairs on the same body, pairs of two polylines and pairs filtered out by their layers never make contact,
+    //nor do pairs without a dynamic body, such as a kinematic platform passing a wall, unless one of them is a sensor
+    pub fn can_collide(&self, other: &ColliderProxy2D) -> bool{
+        self.body != other.body && !(self.polyline && other.polyline) && self.layers.interacts_with(&other.layers)
+            && (self.is_dynamic() || other.is_dynamic() || self.sensor || other.sensor)
+    }
+}
+
+//Pair counts of the latest broad phase update
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct BroadPhaseStats{
+    pub proxies: usize,
+    //pairs with overlapping bounds passed on to the narrow phase
+    pub candidate_pairs: usize,
+    //candidate pairs that weren't candidates in the previous update, and previous candidates that no longer are
+    pub started_pairs: usize,
+    pub ended_pairs: usize,
+    //pairs whose bounds overlapped along x and had to be checked along y, compared to proxies*(proxies - 1)/2 for testing every pair
+    pub overlap_tests: usize,
+    //swaps needed to sort the proxies again, low while colliders move smoothly
+    pub swaps: usize
+}
+
+//Sweep and prune over the bounds of every collider.
+//Proxies stay sorted along x between updates, so re-sorting after the colliders moved only has to fix up the few that passed each other
+#[derive(Resource, Clone, Debug, Default)]
+pub struct BroadPhase2D{
+    //ordered by the lower x bound, then by entity
+    proxies: Vec<ColliderProxy2D>,
+    //position of every entity's proxy
+    indices: HashMap<Entity, usize>,
+    //ordered by entity, with the smaller entity first in each pair
+    pairs: Vec<(Entity, Entity)>,
+    pub stats: BroadPhaseStats
+}
+impl BroadPhase2D{
+    pub const PROXIES: DiagnosticId = DiagnosticId::from_u128(50569633160507263314078950583848271148);
+    pub const CANDIDATE_PAIRS: DiagnosticId = DiagnosticId::from_u128(184447824083428292518535717332913275144);
+    pub const OVERLAP_TESTS: DiagnosticId = DiagnosticId::from_u128(201167542932137879946027235402712308652);
+
+    pub fn pairs(&self) -> &[(Entity, Entity)]{
+        &self.pairs
+    }
+    pub fn proxy(&self, entity: Entity) -> Option<&ColliderProxy2D>{
+        self.indices.get(&entity).map(|index| &self.proxies[*index])
+    }
+    pub fn proxies(&self) -> &[ColliderProxy2D]{
+        &self.proxies
+    }
+
+    //Moves a proxy without touching its bounds or the candidate pairs, for corrections small enough to stay within the bounds
+    pub fn move_proxy(&mut self, entity: Entity, pose: Isometry2D){
+        if let Some(index) = self.indices.get(&entity) {
+            self.proxies[*index].pose = pose;
+        }
+    }
+
+    //Replaces the proxies with <proxies>, dropping colliders that are gone, and finds the candidate pairs
+    pub fn update(&mut self, proxies: impl IntoIterator<Item = ColliderProxy2D>){
+        let mut fresh: HashMap<Entity, ColliderProxy2D> = proxies.into_iter().map(|proxy| (proxy.entity, proxy)).collect();
+        self.proxies.retain_mut(|proxy| match fresh.remove(&proxy.entity) {
+            Some(updated) => {
+                *proxy = updated;
+                true
+            },
+            None => false
+        });
+        let mut spawned: Vec<ColliderProxy2D> = fresh.into_values().collect();
+        spawned.sort_unstable_by_key(|proxy| proxy.entity);
+        self.proxies.extend(spawned);
+
+        let swaps = self.sort();
+
+        let mut pairs = Vec::new();
+        let mut overlap_tests = 0;
+        for (index, a) in self.proxies.iter().enumerate(){
+            for b in self.proxies[index + 1..].iter(){
+                if b.aabb.min.x > a.aabb.max.x {
+                    break;
+                }
+                overlap_tests += 1;
+                if a.aabb.overlaps(&b.aabb) && a.can_collide(b) {
+                    pairs.push(if a.entity < b.entity { (a.entity, b.entity) } else { (b.entity, a.entity) });
+                }
+            }
+        }
+        pairs.sort_unstable();
+
+        self.stats = BroadPhaseStats{
+            proxies: self.proxies.len(),
+            candidate_pairs: pairs.len(),
+            started_pairs: pairs.iter().filter(|pair| self.pairs.binary_search(pair).is_err()).count(),
+            ended_pairs: self.pairs.iter().filter(|pair| pairs.binary_search(pair).is_err()).count(),
+            overlap_tests,
+            swaps
+        };
+        self.pairs = pairs;
+    }
+
+    //Moves proxies to new poses and bounds, keeping the candidate pairs, so queries see where the solver left the colliders
+    pub fn refit(&mut self, proxies: impl IntoIterator<Item = (Entity, Isometry2D, Aabb2D)>){
+        for (entity, pose, aabb) in proxies{
+            if let Some(index) = self.indices.get(&entity) {
+                self.proxies[*index].pose = pose;
+                self.proxies[*index].aabb = aabb;
+            }
+        }
+        self.sort();
+    }
+
+    //Insertion sort, close to linear on the nearly sorted order left by the last update. Returns the swaps it took
+    fn sort(&mut self) -> usize{
+        let mut swaps = 0;
+        for index in 1..self.proxies.len(){
+            let mut position = index;
+            while position > 0 && Self::sorts_before(&self.proxies[position], &self.proxies[position - 1]) {
+                self.proxies.swap(position, position - 1);
+                position -= 1;
+                swaps += 1;
+            }
+        }
+        self.indices = self.proxies.iter().enumerate().map(|(index, proxy)| (proxy.entity, index)).collect();
+        swaps
+    }
+
+    fn sorts_before(a: &ColliderProxy2D, b: &ColliderProxy2D) -> bool{
+        a.aabb.min.x < b.aabb.min.x || (a.aabb.min.x == b.aabb.min.x && a.entity < b.entity)
+    }
+}
+
+//Places every collider at the new state and refreshes the candidate pairs for detect_contacts.
+//Colliders on bodies with ContinuousCollision2D are bounded over their whole motion during the step
+#[allow(clippy::type_complexity)]
+pub fn update_broad_phase(
+    colliders: Query<(Entity, ColliderComponents, Option<&Parent>, &Transform, &GlobalTransform, Option<&CollisionLayers2D>, Has<Sensor2D>)>,
+    bodies: Query<(&SimulationData, &RigidBody2D)>,
+    continuous: Query<(), With<ContinuousCollision2D>>,
+    sleeping: Query<(), With<Sleeping>>,
+    mut broad_phase: ResMut<BroadPhase2D>,
+    mut diagnostics: Diagnostics
+){
+    broad_phase.update(colliders.iter().map(|(entity, components, parent, transform, global_transform, layers, sensor)| {
+        let (body, pose) = collider_pose(entity, parent, transform, global_transform, &bodies);
+        let collider = Collider2D::from_components(components);
+        let mut aabb = collider_aabb(collider, pose);
+        if let Some((data, rigid_body)) = body.filter(|body| continuous.contains(*body)).and_then(|body| bodies.get(body).ok()) {
+            let sweep = Sweep2D::new(data, rigid_body);
+            let local = sweep.pose(1.0).inverse().mul(pose);
+            aabb = aabb.union(&collider_aabb(collider, sweep.pose(0.0).mul(local)));
+        }
+        ColliderProxy2D{
+            entity,
+            body,
+            body_type: body.and_then(|body| bodies.get(body).ok()).map_or(RigidBodyType2D::Static, |(_, body)| body.body_type),
+            pose,
+            aabb,
+            polyline: matches!(collider, Collider2D::Polyline(_)),
+            layers: layers.copied().unwrap_or_default(),
+            sensor,
+            sleeping: body.is_some_and(|body| sleeping.contains(body))
+        }
+    }));
+    let stats = broad_phase.stats;
+    diagnostics.add_measurement(BroadPhase2D::PROXIES, || stats.proxies as f64);
+    diagnostics.add_measurement(BroadPhase2D::CANDIDATE_PAIRS, || stats.candidate_pairs as f64);
+    diagnostics.add_measurement(BroadPhase2D::OVERLAP_TESTS, || stats.overlap_tests as f64);
+}
+
+//Places the colliders of awake bodies where the solver left them, after solve_constraints
+pub fn refit_broad_phase(
+    colliders: Query<(Entity, ColliderComponents, Option<&Parent>, &Transform, &GlobalTransform)>,
+    bodies: Query<(&SimulationData, &RigidBody2D)>,
+    mut broad_phase: ResMut<BroadPhase2D>
+){
+    let moved: Vec<(Entity, Isometry2D, Aabb2D)> = colliders.iter()
+        .filter(|(entity, ..)| broad_phase.proxy(*entity).is_some_and(|proxy| !proxy.is_resting()))
+        .map(|(entity, components, parent, transform, global_transform)| {
+            let (_, pose) = collider_pose(entity, parent, transform, global_transform, &bodies);
+            (entity, pose, collider_aabb(Collider2D::from_components(components), pose))
+        })
+        .collect();
+    broad_phase.refit(moved);
+}
@@ -0,0 +1,154 @@
+use super::*;
+
+const TIME_OF_IMPACT_MAX_ITERATIONS: usize = 32;
+
+//Motion of a body over the last simulation step, with its centre of mass moving in a straight line and turning at a constant rate
+#[derive(Clone, Copy, Debug)]
+pub struct Sweep2D{
+    pub start: RigidBodyState2D,
+    pub end: RigidBodyState2D,
+    pub center_of_mass: Vector
+}
+impl Sweep2D{
+    pub fn new(data: &SimulationData, body: &RigidBody2D) -> Self{
+        Self{start: data.previous, end: data.state, center_of_mass: body.center_of_mass}
+    }
+    //State of the centre of mass at <time>, from 0 at the start of the step to 1 at the end
+    pub fn state(&self, time: Scalar) -> RigidBodyState2D{
+        self.start + (self.end - self.start)*time
+    }
+    //Pose of the body's origin at <time>
+    pub fn pose(&self, time: Scalar) -> Isometry2D{
+        let origin = self.state(time).shifted(-self.center_of_mass);
+        Isometry2D::new(origin.position, origin.angle)
+    }
+}
+
+//First moment a collider following a sweep comes within <target> of a collider that stays put
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TimeOfImpact{
+    //fraction of the step
+    pub time: Scalar,
+    //on the surface of the moving collider
+    pub point: Vector,
+    //from the moving collider to the other one
+    pub normal: Vector,
+    //distance left between the surfaces
+    pub separation: Scalar
+}
+
+//Sent when a body with ContinuousCollision2D would have moved into another collider during the step and was stopped at the moment of impact
+#[derive(Event, Clone, Debug)]
+pub struct TimeOfImpact2D{
+    pub body: Entity,
+    pub collider: Entity,
+    pub other: Entity,
+    pub impact: TimeOfImpact
+}
+
+//Closest surfaces between two sets of convex pieces
+fn closest_parts(a: &[ConvexShape2D], b: &[ConvexShape2D]) -> Option<SurfaceDistance2D>{
+    a.iter()
+        .flat_map(|part_a| b.iter().map(move |part_b| surface_distance(part_a, part_b)))
+        .reduce(|closest, distance| if distance.separation < closest.separation { distance } else { closest })
+}
+
+//Time of impact by conservative advancement: the collider is moved forward by the distance to the other collider divided by
+//an upper bound on how fast any of its points moves, which can never step past the first contact.
+//<local> places the collider on the swept body. Returns None if the colliders don't meet during the step, or already touch at its start
+pub fn time_of_impact(
+    collider: Collider2D,
+    local: Isometry2D,
+    sweep: &Sweep2D,
+    other: Collider2D,
+    other_pose: Isometry2D,
+    target: Scalar
+) -> Option<TimeOfImpact>{
+    let other_parts = convex_parts(other, other_pose);
+    let end_parts = convex_parts(collider, sweep.pose(1.0).mul(local));
+    let reach = end_parts.iter()
+        .flat_map(|part| part.vertices.iter().map(move |vertex| vertex.distance(sweep.end.position) + part.radius))
+        .fold(0.0, Scalar::max);
+    let speed_bound = sweep.end.position.distance(sweep.start.position) + (sweep.end.angle - sweep.start.angle).abs()*reach;
+    if speed_bound <= Scalar::EPSILON {
+        return None;
+    }
+    let tolerance = 0.25*target;
+    let mut time: Scalar = 0.0;
+    for _ in 0..TIME_OF_IMPACT_MAX_ITERATIONS{
+        let parts = convex_parts(collider, sweep.pose(time).mul(local));
+        let closest = closest_parts(&parts, &other_parts)?;
+        if closest.separation <= target + tolerance {
+            if time == 0.0 {
+                return None;
+            }
+            return Some(TimeOfImpact{time, point: closest.point_a, normal: closest.normal, separation: closest.separation});
+        }
+        time += (closest.separation - target)/speed_bound;
+        if time >= 1.0 {
+            return None;
+        }
+    }
+    None
+}
+
+//Stops dynamic bodies with ContinuousCollision2D at their first impact along the step, so they don't tunnel through thin colliders.
+//The body is left overlapping the collider it hit by half the allowed penetration, which lets detect_contacts and the contact solver
+//handle the collision as usual. Other colliders are treated as resting at their state at the end of the step
+#[allow(clippy::type_complexity)]
+pub fn solve_continuous_collisions(
+    config: Res<ContactSolverConfig>,
+    mut broad_phase: ResMut<BroadPhase2D>,
+    colliders: Query<ColliderComponents>,
+    mut bodies: Query<(Entity, &mut SimulationData, &RigidBody2D), (With<ContinuousCollision2D>, Without<Sleeping>)>,
+    mut impacts: EventWriter<TimeOfImpact2D>
+){
+    let mut swept: Vec<(Entity, Mut<SimulationData>, &RigidBody2D)> = bodies.iter_mut().filter(|(_, _, body)| body.is_dynamic()).collect();
+    swept.sort_unstable_by_key(|(entity, ..)| *entity);
+    for (entity, mut data, body) in swept{
+        let sweep = Sweep2D::new(&data, body);
+        let origin = sweep.pose(1.0).inverse();
+        let mut first: Option<TimeOfImpact2D> = None;
+        for (collider_a, collider_b) in broad_phase.pairs().iter().copied(){
+            let (Some(proxy_a), Some(proxy_b)) = (broad_phase.proxy(collider_a), broad_phase.proxy(collider_b)) else {
+                continue;
+            };
+            let (moving, other) = if proxy_a.body == Some(entity) { (proxy_a, proxy_b) } else if proxy_b.body == Some(entity) { (proxy_b, proxy_a) } else {
+                continue;
+            };
+            if moving.sensor || other.sensor {
+                continue;
+            }
+            let (Ok(a), Ok(b)) = (colliders.get(moving.entity), colliders.get(other.entity)) else {
+                continue;
+            };
+            let impact = time_of_impact(
+                Collider2D::from_components(a),
+                origin.mul(moving.pose),
+                &sweep,
+                Collider2D::from_components(b),
+                other.pose,
+                0.5*config.allowed_penetration
+            );
+            if let Some(impact) = impact.filter(|impact| first.as_ref().map_or(true, |first| impact.time < first.impact.time)) {
+                first = Some(TimeOfImpact2D{body: entity, collider: moving.entity, other: other.entity, impact});
+            }
+        }
+        let Some(first) = first else {
+            continue;
+        };
+        let mut state = sweep.state(first.impact.time);
+        state.position += first.impact.normal*(first.impact.separation + 0.5*config.allowed_penetration);
+        data.state.position = state.position;
+        data.state.angle = state.angle;
+        let pose = Sweep2D{end: data.state, ..sweep}.pose(1.0);
+        let moved: Vec<(Entity, Isometry2D)> = broad_phase.proxies().iter()
+            .filter(|proxy| proxy.body == Some(entity))
+            .map(|proxy| (proxy.entity, pose.mul(origin.mul(proxy.pose))))
+            .collect();
+        for (collider, pose) in moved{
+            broad_phase.move_proxy(collider, pose);
+        }
+        impacts.send(first);
+    }
+}
@@ -0,0 +1,59 @@
+use super::*;
+
+//Sent in the step two colliders start touching, with the first of their manifolds
+#[derive(Event, Clone, Debug)]
+pub struct CollisionStarted{
+    pub a: Entity,
+    pub b: Entity,
+    pub manifold: ContactManifold2D
+}
+
+//Sent in the step two colliders stop touching, including when one of them is despawned
+#[derive(Event, Clone, Copy, Debug)]
+pub struct CollisionEnded{
+    pub a: Entity,
+    pub b: Entity
+}
+
+//Collider pairs touching at the end of the last step, which report_collisions compares the next contacts against.
+//Ordered like Contacts2D, by collider pair
+#[derive(Resource, Clone, Debug, Default)]
+pub struct TouchingPairs2D(pub Vec<(Entity, Entity)>);
+
+//Compares the touching pairs in Contacts2D with the ones of the previous step, sending events and updating CollidingEntities
+pub fn report_collisions(
+    contacts: Res<Contacts2D>,
+    mut touching: ResMut<TouchingPairs2D>,
+    mut colliding: Query<(Entity, &mut CollidingEntities)>,
+    mut started: EventWriter<CollisionStarted>,
+    mut ended: EventWriter<CollisionEnded>
+){
+    //contacts are ordered by collider pair, so the first contact of a pair carries its first manifold
+    let mut firsts: Vec<&Contact2D> = contacts.contacts.iter().collect();
+    firsts.dedup_by_key(|contact| (contact.collider_a, contact.collider_b));
+    let current: Vec<(Entity, Entity)> = firsts.iter().map(|contact| (contact.collider_a, contact.collider_b)).collect();
+    for (a, b) in touching.0.iter().copied().filter(|pair| current.binary_search(pair).is_err()){
+        for (entity, other) in [(a, b), (b, a)]{
+            if let Ok((_, mut colliding)) = colliding.get_mut(entity) {
+                colliding.0.remove(&other);
+            }
+        }
+        ended.send(CollisionEnded{a, b});
+    }
+    for contact in firsts.into_iter().filter(|contact| touching.0.binary_search(&(contact.collider_a, contact.collider_b)).is_err()){
+        let (a, b) = (contact.collider_a, contact.collider_b);
+        for (entity, other) in [(a, b), (b, a)]{
+            if let Ok((_, mut colliding)) = colliding.get_mut(entity) {
+                colliding.0.insert(other);
+            }
+        }
+        started.send(CollisionStarted{a, b, manifold: contact.manifold.clone()});
+    }
+    //components added since the last step start out with every pair already touching
+    for (entity, mut colliding) in colliding.iter_mut().filter(|(_, colliding)| colliding.is_added()){
+        colliding.0 = current.iter()
+            .filter_map(|(a, b)| if *a == entity { Some(*b) } else if *b == entity { Some(*a) } else { None })
+            .collect();
+    }
+    touching.0 = current;
+}
@@ -0,0 +1,149 @@
+use super::*;
+
+const GJK_MAX_ITERATIONS: usize = 20;
+//search directions shorter than this mean the origin lies on the simplex, so the hulls touch
+const GJK_TOLERANCE: Scalar = 1e-6;
+
+//Closest points between two convex hulls, with distance 0 when they overlap
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ClosestPoints2D{
+    pub point_a: Vector,
+    pub point_b: Vector,
+    pub distance: Scalar
+}
+
+//Vertex of the Minkowski difference b - a, with its barycentric weight in the current closest point
+#[derive(Clone, Copy, Debug)]
+struct SimplexVertex{
+    a: Vector,
+    b: Vector,
+    w: Vector,
+    weight: Scalar,
+    index_a: usize,
+    index_b: usize
+}
+
+//Distance between the convex hulls of two point sets by the Gilbert-Johnson-Keerthi algorithm, as in Box2D's b2Distance
+pub fn gjk_distance(a: &[Vector], b: &[Vector]) -> ClosestPoints2D{
+    let vertex = |index_a: usize, index_b: usize| SimplexVertex{
+        a: a[index_a], 
+        b: b[index_b], 
+        w: b[index_b] - a[index_a], 
+        weight: 1.0, 
+        index_a, 
+        index_b
+    };
+    let mut simplex = vec![vertex(0, 0)];
+    for _ in 0..GJK_MAX_ITERATIONS{
+        match simplex.len() {
+            2 => solve_segment(&mut simplex),
+            3 => solve_triangle(&mut simplex),
+            _ => ()
+        }
+        if simplex.len() == 3 {
+            break;
+        }
+        let direction = -simplex.iter().fold(Vector::ZERO, |closest, vertex| closest + vertex.w*vertex.weight);
+        if direction.length_squared() < GJK_TOLERANCE*GJK_TOLERANCE {
+            break;
+        }
+        let (index_a, index_b) = (support(a, -direction), support(b, direction));
+        if simplex.iter().any(|vertex| vertex.index_a == index_a && vertex.index_b == index_b) {
+            break;
+        }
+        //a support point no closer to the origin, as on parallel faces, would only add a degenerate triangle
+        let next = vertex(index_a, index_b);
+        if next.w.dot(direction) + direction.length_squared() <= GJK_TOLERANCE*direction.length_squared() {
+            break;
+        }
+        simplex.push(next);
+    }
+    let point_a = simplex.iter().fold(Vector::ZERO, |point, vertex| point + vertex.a*vertex.weight);
+    let point_b = simplex.iter().fold(Vector::ZERO, |point, vertex| point + vertex.b*vertex.weight);
+    let distance = if simplex.len() == 3 { 0.0 } else { point_a.distance(point_b) };
+    ClosestPoints2D{point_a, point_b, distance}
+}
+
+//Closest points between the surfaces of two rounded convex shapes
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SurfaceDistance2D{
+    //negative where the rounded shapes overlap, and 0 once their cores do
+    pub separation: Scalar,
+    pub point_a: Vector,
+    pub point_b: Vector,
+    //from the core of a to the core of b, zero once the cores overlap
+    pub normal: Vector
+}
+
+pub fn surface_distance(a: &ConvexShape2D, b: &ConvexShape2D) -> SurfaceDistance2D{
+    let closest = gjk_distance(&a.vertices, &b.vertices);
+    if closest.distance <= 0.0 {
+        return SurfaceDistance2D{separation: 0.0, point_a: closest.point_a, point_b: closest.point_b, normal: Vector::ZERO};
+    }
+    let normal = (closest.point_b - closest.point_a)/closest.distance;
+    SurfaceDistance2D{
+        separation: closest.distance - a.radius - b.radius,
+        point_a: closest.point_a + normal*a.radius,
+        point_b: closest.point_b - normal*b.radius,
+        normal
+    }
+}
+
+//Index of the point furthest along <direction>
+pub(crate) fn support(points: &[Vector], direction: Vector) -> usize{
+    (1..points.len()).fold(0, |best, index| if points[index].dot(direction) > points[best].dot(direction) { index } else { best })
+}
+
+//Reduces a segment simplex to the feature closest to the origin and weighs its vertices
+fn solve_segment(simplex: &mut Vec<SimplexVertex>){
+    let (w1, w2) = (simplex[0].w, simplex[1].w);
+    let edge = w2 - w1;
+    let weight_2 = -w1.dot(edge);
+    if weight_2 <= 0.0 {
+        simplex.truncate(1);
+        simplex[0].weight = 1.0;
+        return;
+    }
+    let weight_1 = w2.dot(edge);
+    if weight_1 <= 0.0 {
+        simplex.remove(0);
+        simplex[0].weight = 1.0;
+        return;
+    }
+    let total = weight_1 + weight_2;
+    simplex[0].weight = weight_1/total;
+    simplex[1].weight = weight_2/total;
+}
+
+//Reduces a triangle simplex to the feature closest to the origin, keeping all three vertices when it contains the origin
+fn solve_triangle(simplex: &mut Vec<SimplexVertex>){
+    let (v1, v2, v3) = (simplex[0], simplex[1], simplex[2]);
+    let (w1, w2, w3) = (v1.w, v2.w, v3.w);
+    let (e12, e13, e23) = (w2 - w1, w3 - w1, w3 - w2);
+    let (d12_1, d12_2) = (w2.dot(e12), -w1.dot(e12));
+    let (d13_1, d13_2) = (w3.dot(e13), -w1.dot(e13));
+    let (d23_1, d23_2) = (w3.dot(e23), -w2.dot(e23));
+    let area = e12.perp_dot(e13);
+    let d123_1 = area*w2.perp_dot(w3);
+    let d123_2 = area*w3.perp_dot(w1);
+    let d123_3 = area*w1.perp_dot(w2);
+    let weighted = |vertices: &[(SimplexVertex, Scalar)]| -> Vec<SimplexVertex> {
+        let total: Scalar = vertices.iter().map(|(_, weight)| weight).sum();
+        vertices.iter().map(|(vertex, weight)| SimplexVertex{weight: weight/total, ..*vertex}).collect()
+    };
+    *simplex = if d12_2 <= 0.0 && d13_2 <= 0.0 {
+        weighted(&[(v1, 1.0)])
+    } else if d12_1 > 0.0 && d12_2 > 0.0 && d123_3 <= 0.0 {
+        weighted(&[(v1, d12_1), (v2, d12_2)])
+    } else if d13_1 > 0.0 && d13_2 > 0.0 && d123_2 <= 0.0 {
+        weighted(&[(v1, d13_1), (v3, d13_2)])
+    } else if d12_1 <= 0.0 && d23_2 <= 0.0 {
+        weighted(&[(v2, 1.0)])
+    } else if d13_1 <= 0.0 && d23_1 <= 0.0 {
+        weighted(&[(v3, 1.0)])
+    } else if d23_1 > 0.0 && d23_2 > 0.0 && d123_1 <= 0.0 {
+        weighted(&[(v2, d23_1), (v3, d23_2)])
+    } else {
+        weighted(&[(v1, d123_1), (v2, d123_2), (v3, d123_3)])
+    };
+}
@@ -0,0 +1,336 @@
+use super::*;
+
+//slots of Joint2D::impulses
+const POINT_X: usize = 0;
+const POINT_Y: usize = 1;
+const ANGLE: usize = 2;
+const AXIAL: usize = 3;
+const LOWER: usize = 4;
+const UPPER: usize = 5;
+const MOTOR: usize = 6;
+const JOINT_IMPULSES: usize = 7;
+
+//Range a joint's angle or translation is kept within
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct JointLimits2D{
+    pub lower: Scalar,
+    pub upper: Scalar
+}
+impl JointLimits2D{
+    pub fn new(lower: Scalar, upper: Scalar) -> Self{
+        Self{lower, upper}
+    }
+}
+
+//Drives a joint towards <target_speed>, in radians or units per second, with at most <max_force> (a torque for revolute joints)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct JointMotor2D{
+    pub target_speed: Scalar,
+    pub max_force: Scalar
+}
+impl JointMotor2D{
+    pub fn new(target_speed: Scalar, max_force: Scalar) -> Self{
+        Self{target_speed, max_force}
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum JointKind2D{
+    //keeps the anchors exactly <length> apart, like a rigid rod
+    Distance{length: Scalar},
+    //keeps the anchors at most <max_length> apart and lets them move freely closer than that
+    Rope{max_length: Scalar},
+    //pulls the anchors towards <rest_length> with an oscillation of <frequency> hertz and <damping_ratio>, 1 for critical damping.
+    //Unlike DampedSpring2D it is solved as a soft constraint, which stays stable at any stiffness
+    Spring{rest_length: Scalar, frequency: Scalar, damping_ratio: Scalar},
+    //pins the anchors together and lets the bodies turn around them, limiting the angle between them to <limits>
+    Revolute{limits: Option<JointLimits2D>, motor: Option<JointMotor2D>},
+    //lets the anchors slide apart along <local_axis> only, without the bodies turning, limiting the translation to <limits>.
+    //The axis is in the frame of body_b, or in world space without body_b
+    Prismatic{local_axis: Vector, limits: Option<JointLimits2D>, motor: Option<JointMotor2D>},
+    //pins the anchors together and stops the bodies from turning relative to each other
+    Weld
+}
+
+//Constraint between an anchor on <body_a> and an anchor on <body_b>, solved together with the contacts.
+//Without <body_b>, <anchor_b> is a point in world space and the joint holds body_a to it.
+//A <body_b> without SimulationData, such as a static collider, holds still and places <anchor_b> with its GlobalTransform.
+//Angles, translations and motor speeds are those of body_a relative to body_b
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Joint2D{
+    pub kind: JointKind2D,
+    pub body_a: Entity,
+    //relative to the transform origin of body_a
+    pub local_anchor_a: Vector,
+    pub body_b: Option<Entity>,
+    pub anchor_b: Vector,
+    //angle of body_a relative to body_b that revolute limits, prismatic and weld joints measure from
+    pub reference_angle: Scalar,
+    //impulses the solver applied in the last step, carried over to warm start the next one
+    impulses: [Scalar; JOINT_IMPULSES]
+}
+impl Joint2D{
+    pub fn new(kind: JointKind2D, body_a: Entity, local_anchor_a: Vector, body_b: Option<Entity>, anchor_b: Vector) -> Self{
+        Self{kind, body_a, local_anchor_a, body_b, anchor_b, reference_angle: 0.0, impulses: [0.0; JOINT_IMPULSES]}
+    }
+    pub fn with_reference_angle(mut self, reference_angle: Scalar) -> Self{
+        self.reference_angle = reference_angle;
+        self
+    }
+    //Forgets the impulses of the last step, for joints changed so much that they are no useful guess
+    pub fn reset_impulses(&mut self){
+        self.impulses = [0.0; JOINT_IMPULSES];
+    }
+}
+
+/*
+    SOLVER ROWS
+*/
+
+//How a row's accumulated impulse is bounded
+#[derive(Clone, Copy, Debug)]
+enum RowBound{
+    Equality,
+    //the row's error may only grow, with an impulse of 0 or more
+    Lower,
+    Clamped(Scalar),
+    Soft{gamma: Scalar}
+}
+
+//One scalar constraint of a joint with Jacobian (-linear, -angular_a, linear, angular_b)
+#[derive(Clone, Copy, Debug)]
+struct JointRow2D{
+    slot: usize,
+    linear: Vector,
+    angular_a: Scalar,
+    angular_b: Scalar,
+    mass: Scalar,
+    //constraint error at the start of the solve
+    error: Scalar,
+    //added to the constraint speed before solving for the impulse
+    bias: Scalar,
+    bound: RowBound
+}
+impl JointRow2D{
+    fn new(slot: usize, linear: Vector, angular_a: Scalar, angular_b: Scalar, error: Scalar, a: &SolverBody2D, b: &SolverBody2D) -> Self{
+        let k = a.inverse_mass + b.inverse_mass + a.inverse_inertia*angular_a*angular_a + b.inverse_inertia*angular_b*angular_b;
+        Self{slot, linear, angular_a, angular_b, mass: if k > 0.0 { k.recip() } else { 0.0 }, error, bias: 0.0, bound: RowBound::Equality}
+    }
+    fn angular(slot: usize, sign: Scalar, error: Scalar, a: &SolverBody2D, b: &SolverBody2D) -> Self{
+        Self::new(slot, Vector::ZERO, sign, sign, error, a, b)
+    }
+    //Inequality keeping the error at 0 or more, which lets the bodies close any gap within a single step
+    fn lower(mut self, dt: Scalar) -> Self{
+        self.bound = RowBound::Lower;
+        self.bias = self.error.max(0.0)/dt;
+        self
+    }
+    fn motor(mut self, motor: JointMotor2D, dt: Scalar) -> Self{
+        self.bound = RowBound::Clamped(motor.max_force*dt);
+        self.bias = -motor.target_speed;
+        self
+    }
+    //Spring and damper written as a constraint with compliance, as in Box2D's soft constraints
+    fn soft(mut self, frequency: Scalar, damping_ratio: Scalar, dt: Scalar) -> Self{
+        let omega = 2.0*PI*frequency;
+        let (stiffness, damping) = (self.mass*omega*omega, 2.0*self.mass*damping_ratio*omega);
+        let gamma = dt*(damping + dt*stiffness);
+        let gamma = if gamma > 0.0 { gamma.recip() } else { 0.0 };
+        self.bias = self.error*dt*stiffness*gamma;
+        let k = if self.mass > 0.0 { self.mass.recip() } else { 0.0 };
+        self.mass = if k + gamma > 0.0 { (k + gamma).recip() } else { 0.0 };
+        self.bound = RowBound::Soft{gamma};
+        self
+    }
+    fn speed(&self, a: &SolverBody2D, b: &SolverBody2D) -> Scalar{
+        self.linear.dot(b.velocity - a.velocity) + self.angular_b*b.angular_velocity - self.angular_a*a.angular_velocity
+    }
+    //Change of the error from the position corrections made so far
+    fn moved(&self, a: &SolverBody2D, b: &SolverBody2D) -> Scalar{
+        self.linear.dot(b.translation - a.translation) + self.angular_b*b.rotation - self.angular_a*a.rotation
+    }
+    fn apply(&self, a: &mut SolverBody2D, b: &mut SolverBody2D, impulse: Scalar){
+        a.velocity -= self.linear*(impulse*a.inverse_mass);
+        a.angular_velocity -= self.angular_a*impulse*a.inverse_inertia;
+        b.velocity += self.linear*(impulse*b.inverse_mass);
+        b.angular_velocity += self.angular_b*impulse*b.inverse_inertia;
+    }
+    fn apply_position(&self, a: &mut SolverBody2D, b: &mut SolverBody2D, impulse: Scalar){
+        a.translation -= self.linear*(impulse*a.inverse_mass);
+        a.rotation -= self.angular_a*impulse*a.inverse_inertia;
+        b.translation += self.linear*(impulse*b.inverse_mass);
+        b.rotation += self.angular_b*impulse*b.inverse_inertia;
+    }
+}
+
+//Two dimensional constraint pinning two anchors together, solved as a block
+#[derive(Clone, Copy, Debug)]
+struct PointRow2D{
+    arm_a: Vector,
+    arm_b: Vector,
+    //symmetric effective mass matrix (k11, k12, k22)
+    k: (Scalar, Scalar, Scalar),
+    error: Vector
+}
+impl PointRow2D{
+    fn new(arm_a: Vector, arm_b: Vector, error: Vector, a: &SolverBody2D, b: &SolverBody2D) -> Self{
+        let mass = a.inverse_mass + b.inverse_mass;
+        Self{
+            arm_a,
+            arm_b,
+            k: (
+                mass + a.inverse_inertia*arm_a.y*arm_a.y + b.inverse_inertia*arm_b.y*arm_b.y,
+                -a.inverse_inertia*arm_a.x*arm_a.y - b.inverse_inertia*arm_b.x*arm_b.y,
+                mass + a.inverse_inertia*arm_a.x*arm_a.x + b.inverse_inertia*arm_b.x*arm_b.x
+            ),
+            error
+        }
+    }
+    //Impulse that changes the relative anchor velocity by <change>
+    fn solve(&self, change: Vector) -> Vector{
+        let (k11, k12, k22) = self.k;
+        let determinant = k11*k22 - k12*k12;
+        if determinant == 0.0 {
+            return Vector::ZERO;
+        }
+        Vector::new(k22*change.x - k12*change.y, k11*change.y - k12*change.x)/determinant
+    }
+}
+
+//A joint prepared for the solve, between the solver bodies at <a> and <b>.
+//The rows measure b relative to a, so a is the solver body of body_b, or the static body for world anchors, and b the one of body_a
+#[derive(Clone, Debug)]
+pub(super) struct SolverJoint2D{
+    pub a: usize,
+    pub b: usize,
+    point: Option<PointRow2D>,
+    rows: Vec<JointRow2D>
+}
+impl SolverJoint2D{
+    //<a> holds the solver body of body_b and <b> the one of body_a, with angle and centre of mass filled in
+    pub fn new(joint: &Joint2D, (index_a, a): (usize, &SolverBody2D), (index_b, b): (usize, &SolverBody2D), dt: Scalar) -> Self{
+        let arm_a = match joint.body_b {
+            Some(_) => Vector::from_angle(a.angle).rotate(joint.anchor_b - a.center_of_mass),
+            None => joint.anchor_b - a.center
+        };
+        let arm_b = Vector::from_angle(b.angle).rotate(joint.local_anchor_a - b.center_of_mass);
+        let offset = (b.center + arm_b) - (a.center + arm_a);
+        let length = offset.length();
+        let axis = if length > Scalar::EPSILON { offset/length } else { Vector::X };
+        let angle = b.angle - a.angle - joint.reference_angle;
+        let axial = |slot: usize, direction: Vector, error: Scalar| {
+            JointRow2D::new(slot, direction, arm_a.perp_dot(direction), arm_b.perp_dot(direction), error, a, b)
+        };
+        let mut point = None;
+        let mut rows = Vec::new();
+        match joint.kind {
+            JointKind2D::Distance{length: target} => rows.push(axial(AXIAL, axis, length - target)),
+            JointKind2D::Rope{max_length} => rows.push(axial(AXIAL, -axis, max_length - length).lower(dt)),
+            JointKind2D::Spring{rest_length, frequency, damping_ratio} => {
+                if frequency > 0.0 {
+                    rows.push(axial(AXIAL, axis, length - rest_length).soft(frequency, damping_ratio, dt));
+                }
+            },
+            JointKind2D::Revolute{limits, motor} => {
+                point = Some(PointRow2D::new(arm_a, arm_b, offset, a, b));
+                if let Some(limits) = limits {
+                    rows.push(JointRow2D::angular(LOWER, 1.0, angle - limits.lower, a, b).lower(dt));
+                    rows.push(JointRow2D::angular(UPPER, -1.0, limits.upper - angle, a, b).lower(dt));
+                }
+                if let Some(motor) = motor {
+                    rows.push(JointRow2D::angular(MOTOR, 1.0, 0.0, a, b).motor(motor, dt));
+                }
+            },
+            JointKind2D::Prismatic{local_axis, limits, motor} => {
+                let axis = Vector::from_angle(a.angle).rotate(local_axis.normalize_or_zero());
+                //the axis turns with body_a, so its rotation moves the far anchor as if it were at the end of <offset>
+                let sliding = |slot: usize, direction: Vector, error: Scalar| {
+                    JointRow2D::new(slot, direction, (offset + arm_a).perp_dot(direction), arm_b.perp_dot(direction), error, a, b)
+                };
+                let translation = axis.dot(offset);
+                rows.push(sliding(AXIAL, axis.perp(), axis.perp().dot(offset)));
+                rows.push(JointRow2D::angular(ANGLE, 1.0, angle, a, b));
+                if let Some(limits) = limits {
+                    rows.push(sliding(LOWER, axis, translation - limits.lower).lower(dt));
+                    rows.push(sliding(UPPER, -axis, limits.upper - translation).lower(dt));
+                }
+                if let Some(motor) = motor {
+                    rows.push(sliding(MOTOR, axis, 0.0).motor(motor, dt));
+                }
+            },
+            JointKind2D::Weld => {
+                point = Some(PointRow2D::new(arm_a, arm_b, offset, a, b));
+                rows.push(JointRow2D::angular(ANGLE, 1.0, angle, a, b));
+            }
+        }
+        Self{a: index_a, b: index_b, point, rows}
+    }
+
+    pub fn warm_start(&self, joint: &mut Joint2D, a: &mut SolverBody2D, b: &mut SolverBody2D, warm_starting: bool){
+        if !warm_starting {
+            joint.reset_impulses();
+        }
+        //impulses of rows the joint no longer has, such as limits that were removed, would otherwise linger
+        let mut used = [false; JOINT_IMPULSES];
+        if let Some(point) = &self.point {
+            let impulse = Vector::new(joint.impulses[POINT_X], joint.impulses[POINT_Y]);
+            a.apply_impulse(point.arm_a, -impulse);
+            b.apply_impulse(point.arm_b, impulse);
+            used[POINT_X] = true;
+            used[POINT_Y] = true;
+        }
+        for row in self.rows.iter(){
+            row.apply(a, b, joint.impulses[row.slot]);
+            used[row.slot] = true;
+        }
+        for (impulse, used) in joint.impulses.iter_mut().zip(used){
+            if !used {
+                *impulse = 0.0;
+            }
+        }
+    }
+
+    pub fn solve_velocity(&self, joint: &mut Joint2D, a: &mut SolverBody2D, b: &mut SolverBody2D){
+        //limits and motors first, so the equality rows solved last are the ones holding the joint together
+        for row in self.rows.iter().rev(){
+            let accumulated = joint.impulses[row.slot];
+            let gamma = match row.bound { RowBound::Soft{gamma} => gamma, _ => 0.0 };
+            let impulse = -row.mass*(row.speed(a, b) + row.bias + gamma*accumulated);
+            let clamped = match row.bound {
+                RowBound::Lower => (accumulated + impulse).max(0.0),
+                RowBound::Clamped(max) => (accumulated + impulse).clamp(-max, max),
+                RowBound::Equality | RowBound::Soft{..} => accumulated + impulse
+            };
+            joint.impulses[row.slot] = clamped;
+            row.apply(a, b, clamped - accumulated);
+        }
+        if let Some(point) = &self.point {
+            let relative = b.velocity_at(point.arm_b) - a.velocity_at(point.arm_a);
+            let impulse = point.solve(-relative);
+            joint.impulses[POINT_X] += impulse.x;
+            joint.impulses[POINT_Y] += impulse.y;
+            a.apply_impulse(point.arm_a, -impulse);
+            b.apply_impulse(point.arm_b, impulse);
+        }
+    }
+
+    //Removes a fraction of the remaining error of the rigid rows, using the error predicted from the corrections made so far
+    pub fn solve_position(&self, config: &ContactSolverConfig, a: &mut SolverBody2D, b: &mut SolverBody2D){
+        for row in self.rows.iter(){
+            let error = row.error + row.moved(a, b);
+            let correction = match row.bound {
+                RowBound::Equality => (config.position_correction*error).clamp(-config.max_position_correction, config.max_position_correction),
+                RowBound::Lower => (config.position_correction*error).clamp(-config.max_position_correction, 0.0),
+                RowBound::Clamped(_) | RowBound::Soft{..} => continue
+            };
+            row.apply_position(a, b, -row.mass*correction);
+        }
+        if let Some(point) = &self.point {
+            let moved = (b.translation + point.arm_b.perp()*b.rotation) - (a.translation + point.arm_a.perp()*a.rotation);
+            let correction = ((point.error + moved)*config.position_correction).clamp_length_max(config.max_position_correction);
+            let impulse = point.solve(-correction);
+            a.apply_position_impulse(point.arm_a, -impulse);
+            b.apply_position_impulse(point.arm_b, impulse);
+        }
+    }
+}
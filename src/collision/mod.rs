@@ -0,0 +1,192 @@
+mod sat;
+mod gjk;
+mod shapes;
+mod solver;
+mod broad_phase;
+mod ccd;
+mod query;
+mod events;
+mod joints;
+mod sleeping;
+
+use bevy::{prelude::*, utils::HashMap};
+use crate::{components::*, scalar::*, simulation::{SimulationData, RigidBodyState2D, FlatlandTimestep}};
+pub use sat::*;
+pub use gjk::*;
+pub use shapes::*;
+pub use solver::*;
+pub use broad_phase::*;
+pub use ccd::*;
+pub use query::*;
+pub use events::*;
+pub use joints::*;
+pub use sleeping::*;
+
+/*
+    POSES
+*/
+
+//Rigid transform in the plane, used to place colliders from the simulation state rather than the interpolated Transform
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Isometry2D{
+    pub translation: Vector,
+    pub angle: Scalar
+}
+impl Isometry2D{
+    pub fn new(translation: Vector, angle: Scalar) -> Self{
+        Self{translation, angle}
+    }
+    pub fn from_transform(transform: &Transform) -> Self{
+        Self{
+            translation: transform.translation.truncate().to_vector(),
+            angle: transform.rotation.to_euler(EulerRot::ZYX).0 as Scalar
+        }
+    }
+    pub fn rotation(&self) -> Vector{
+        Vector::from_angle(self.angle)
+    }
+    pub fn transform_point(&self, point: Vector) -> Vector{
+        self.translation + self.rotation().rotate(point)
+    }
+    pub fn transform_vector(&self, vector: Vector) -> Vector{
+        self.rotation().rotate(vector)
+    }
+    pub fn inverse_transform_point(&self, point: Vector) -> Vector{
+        Vector::from_angle(-self.angle).rotate(point - self.translation)
+    }
+    pub fn inverse_transform_vector(&self, vector: Vector) -> Vector{
+        Vector::from_angle(-self.angle).rotate(vector)
+    }
+    pub fn inverse(&self) -> Self{
+        Self{translation: -self.inverse_transform_vector(self.translation), angle: -self.angle}
+    }
+    //<local> expressed in the parent frame this isometry places
+    pub fn mul(&self, local: Isometry2D) -> Self{
+        Self{translation: self.transform_point(local.translation), angle: self.angle + local.angle}
+    }
+}
+
+//World pose of a collider and the body it moves with.
+//Colliders on a body or on a direct child of one follow the body's simulation state, any other collider is static and placed by its GlobalTransform
+pub fn collider_pose(
+    entity: Entity, 
+    parent: Option<&Parent>, 
+    transform: &Transform, 
+    global_transform: &GlobalTransform, 
+    bodies: &Query<(&SimulationData, &RigidBody2D)>
+) -> (Option<Entity>, Isometry2D){
+    let body_pose = |(data, body): (&SimulationData, &RigidBody2D)| {
+        let origin = data.state.shifted(-body.center_of_mass);
+        Isometry2D::new(origin.position, origin.angle)
+    };
+    if let Ok(body) = bodies.get(entity) {
+        return (Some(entity), body_pose(body));
+    }
+    if let Some(body) = parent.and_then(|parent| bodies.get(parent.get()).ok()) {
+        return (parent.map(|parent| parent.get()), body_pose(body).mul(Isometry2D::from_transform(transform)));
+    }
+    (None, Isometry2D::from_transform(&global_transform.compute_transform()))
+}
+
+/*
+    CONTACTS
+*/
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ContactPoint2D{
+    //midway between the two surfaces, in world space
+    pub point: Vector,
+    //how far the colliders overlap along the normal
+    pub depth: Scalar,
+    //identifies the pair of features touching, stable between steps while the same features stay in contact
+    pub id: u32,
+    //impulses the contact solver applied along the normal and the tangent, carried over to warm start the next step
+    pub normal_impulse: Scalar,
+    pub tangent_impulse: Scalar
+}
+
+//Up to two points where a pair of colliders touch, sharing one normal pointing from the first collider to the second
+#[derive(Clone, Debug, PartialEq)]
+pub struct ContactManifold2D{
+    pub normal: Vector,
+    pub points: Vec<ContactPoint2D>
+}
+
+impl ContactManifold2D{
+    //Copies the solver impulses of points that were already touching in <last>
+    pub fn warm_start_from(&mut self, last: &ContactManifold2D){
+        for point in self.points.iter_mut(){
+            if let Some(matching) = last.points.iter().find(|matching| matching.id == point.id) {
+                point.normal_impulse = matching.normal_impulse;
+                point.tangent_impulse = matching.tangent_impulse;
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Contact2D{
+    pub collider_a: Entity,
+    pub collider_b: Entity,
+    //None for static colliders
+    pub body_a: Option<Entity>,
+    pub body_b: Option<Entity>,
+    //which pair of convex pieces touch, the segment index when one of the colliders is a polyline and 0 otherwise
+    pub part: u32,
+    pub manifold: ContactManifold2D,
+    //material of the pair, combined by the rules in ContactSolverConfig
+    pub restitution: Scalar,
+    pub friction: Scalar,
+    //either collider is a Sensor2D, so the contact is reported but left alone by the solver
+    pub sensor: bool
+}
+
+//Every touching pair of colliders found in the last simulation step, ordered by collider entity
+#[derive(Resource, Clone, Debug, Default)]
+pub struct Contacts2D{
+    pub contacts: Vec<Contact2D>
+}
+
+//Runs the narrow phase on every candidate pair found by the broad phase.
+//Pairs of static and sleeping colliders keep the contacts they had in the last step, so resting piles cost no narrow phase
+pub fn detect_contacts(
+    config: Res<ContactSolverConfig>,
+    broad_phase: Res<BroadPhase2D>,
+    colliders: Query<ColliderComponents>,
+    mut contacts: ResMut<Contacts2D>
+){
+    let mut previous: HashMap<(Entity, Entity), Vec<Contact2D>> = HashMap::new();
+    for contact in contacts.contacts.drain(..){
+        previous.entry((contact.collider_a, contact.collider_b)).or_default().push(contact);
+    }
+    for (collider_a, collider_b) in broad_phase.pairs().iter().copied(){
+        let (Some(proxy_a), Some(proxy_b)) = (broad_phase.proxy(collider_a), broad_phase.proxy(collider_b)) else {
+            continue;
+        };
+        if proxy_a.is_resting() && proxy_b.is_resting() {
+            contacts.contacts.extend(previous.remove(&(collider_a, collider_b)).unwrap_or_default());
+            continue;
+        }
+        let (Ok(a), Ok(b)) = (colliders.get(collider_a), colliders.get(collider_b)) else {
+            continue;
+        };
+        let (a, b) = (Collider2D::from_components(a), Collider2D::from_components(b));
+        let last = previous.get(&(collider_a, collider_b));
+        for (part, mut manifold) in collide_colliders(a, proxy_a.pose, b, proxy_b.pose){
+            if let Some(last) = last.and_then(|last| last.iter().find(|contact| contact.part == part)) {
+                manifold.warm_start_from(&last.manifold);
+            }
+            contacts.contacts.push(Contact2D{
+                collider_a, 
+                collider_b, 
+                body_a: proxy_a.body, 
+                body_b: proxy_b.body, 
+                part,
+                manifold,
+                restitution: config.restitution_combine.combine(a.restitution(), b.restitution()),
+                friction: config.friction_combine.combine(a.friction(), b.friction()),
+                sensor: proxy_a.sensor || proxy_b.sensor
+            });
+        }
+    }
+}
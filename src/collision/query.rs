@@ -0,0 +1,225 @@
+use bevy::{ecs::system::SystemParam, utils::HashSet};
+use super::*;
+
+const SHAPE_CAST_MAX_ITERATIONS: usize = 32;
+//shapes closer than this count as touching
+const SHAPE_CAST_TOLERANCE: Scalar = 1e-4;
+
+//Where a spatial query met a collider
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SpatialHit2D{
+    pub entity: Entity,
+    //on the surface of the collider
+    pub point: Vector,
+    //outward normal of the collider at <point>
+    pub normal: Vector,
+    //travelled along the ray or cast direction, or from a projected point to the surface, negative inside the collider
+    pub distance: Scalar
+}
+
+//Colliders a spatial query should ignore
+#[derive(Clone, Debug)]
+pub struct SpatialQueryFilter2D{
+    //colliders, and bodies whose colliders are all ignored
+    pub excluded: HashSet<Entity>,
+    //groups of CollisionLayers2D the query hits, colliders that aren't a member of any of them are ignored
+    pub filters: u32,
+    pub include_sensors: bool
+}
+impl Default for SpatialQueryFilter2D{
+    fn default() -> Self {
+        Self{excluded: HashSet::new(), filters: u32::MAX, include_sensors: true}
+    }
+}
+impl SpatialQueryFilter2D{
+    pub fn excluding(entities: impl IntoIterator<Item = Entity>) -> Self{
+        Self{excluded: entities.into_iter().collect(), ..Default::default()}
+    }
+    pub fn with_filters(mut self, filters: u32) -> Self{
+        self.filters = filters;
+        self
+    }
+    pub fn without_sensors(mut self) -> Self{
+        self.include_sensors = false;
+        self
+    }
+    pub fn allows(&self, proxy: &ColliderProxy2D) -> bool{
+        proxy.layers.memberships & self.filters != 0
+            && (self.include_sensors || !proxy.sensor)
+            && !self.excluded.contains(&proxy.entity) 
+            && proxy.body.map_or(true, |body| !self.excluded.contains(&body))
+    }
+}
+
+//Ray casts, shape casts, point projection and overlap queries against every collider.
+//Colliders are found where the latest simulation step left them, after the solver, so ones spawned since then are missed until the next step
+#[derive(SystemParam)]
+pub struct SpatialQuery2D<'w, 's>{
+    broad_phase: Res<'w, BroadPhase2D>,
+    colliders: Query<'w, 's, ColliderComponents>
+}
+impl<'w, 's> SpatialQuery2D<'w, 's>{
+    fn parts(&self, proxy: &ColliderProxy2D) -> Vec<ConvexShape2D>{
+        self.colliders.get(proxy.entity)
+            .map(|components| convex_parts(Collider2D::from_components(components), proxy.pose))
+            .unwrap_or_default()
+    }
+
+    //First collider along the ray, within <max_distance>. A ray starting inside a collider hits it at distance 0
+    pub fn cast_ray(&self, origin: Vector, direction: Vector, max_distance: Scalar, filter: &SpatialQueryFilter2D) -> Option<SpatialHit2D>{
+        let direction = direction.try_normalize()?;
+        let mut first: Option<SpatialHit2D> = None;
+        for proxy in self.broad_phase.proxies().iter().filter(|proxy| filter.allows(proxy)){
+            let limit = first.map_or(max_distance, |first| first.distance);
+            if proxy.aabb.ray_distance(origin, direction, limit).is_none() {
+                continue;
+            }
+            for part in self.parts(proxy){
+                if let Some((distance, normal)) = ray_convex(&part, origin, direction, first.map_or(max_distance, |first| first.distance)) {
+                    first = Some(SpatialHit2D{entity: proxy.entity, point: origin + direction*distance, normal, distance});
+                }
+            }
+        }
+        first
+    }
+
+    //First collider <shape> placed at <pose> runs into when moved along <direction>, within <max_distance>.
+    //The hit point and normal are on the collider that was hit
+    pub fn cast_shape(
+        &self,
+        shape: Collider2D,
+        pose: Isometry2D,
+        direction: Vector,
+        max_distance: Scalar,
+        filter: &SpatialQueryFilter2D
+    ) -> Option<SpatialHit2D>{
+        let direction = direction.try_normalize()?;
+        let parts = convex_parts(shape, pose);
+        let bounds = collider_aabb(shape, pose);
+        let swept = bounds.union(&Aabb2D::new(bounds.min + direction*max_distance, bounds.max + direction*max_distance));
+        let mut first: Option<SpatialHit2D> = None;
+        for proxy in self.broad_phase.proxies().iter().filter(|proxy| filter.allows(proxy) && proxy.aabb.overlaps(&swept)){
+            for other in self.parts(proxy){
+                for part in parts.iter(){
+                    if let Some((distance, point, normal)) = cast_convex(part, &other, direction, first.map_or(max_distance, |first| first.distance)) {
+                        if first.map_or(true, |first| distance < first.distance) {
+                            first = Some(SpatialHit2D{entity: proxy.entity, point, normal, distance});
+                        }
+                    }
+                }
+            }
+        }
+        first
+    }
+
+    //Closest point on the surface of the nearest collider, or of the collider <point> is deepest inside
+    pub fn project_point(&self, point: Vector, filter: &SpatialQueryFilter2D) -> Option<SpatialHit2D>{
+        let mut nearest: Option<SpatialHit2D> = None;
+        for proxy in self.broad_phase.proxies().iter().filter(|proxy| filter.allows(proxy)){
+            if nearest.is_some_and(|nearest| proxy.aabb.distance_to_point(point) > nearest.distance) {
+                continue;
+            }
+            for part in self.parts(proxy){
+                let (distance, surface, normal) = project_convex(&part, point);
+                if nearest.map_or(true, |nearest| distance < nearest.distance) {
+                    nearest = Some(SpatialHit2D{entity: proxy.entity, point: surface, normal, distance});
+                }
+            }
+        }
+        nearest
+    }
+
+    //Colliders whose bounds overlap <aabb>
+    pub fn aabb_intersections(&self, aabb: Aabb2D, filter: &SpatialQueryFilter2D) -> Vec<Entity>{
+        self.broad_phase.proxies().iter()
+            .take_while(|proxy| proxy.aabb.min.x <= aabb.max.x)
+            .filter(|proxy| filter.allows(proxy) && proxy.aabb.overlaps(&aabb))
+            .map(|proxy| proxy.entity)
+            .collect()
+    }
+}
+
+impl ConvexShape2D{
+    pub fn translated(&self, offset: Vector) -> Self{
+        Self{vertices: self.vertices.iter().map(|vertex| *vertex + offset).collect(), ..self.clone()}
+    }
+}
+
+//Ray against the hull grown by the radius, which is bounded by every edge pushed out along its normal and a circle around every vertex.
+//Returns the distance and the outward normal where the ray enters
+fn ray_convex(shape: &ConvexShape2D, origin: Vector, direction: Vector, max_distance: Scalar) -> Option<(Scalar, Vector)>{
+    if gjk_distance(&shape.vertices, &[origin]).distance <= shape.radius {
+        return Some((0.0, -direction));
+    }
+    let mut first: Option<(Scalar, Vector)> = None;
+    let mut found = |distance: Scalar, normal: Vector| {
+        if (0.0..=max_distance).contains(&distance) && first.map_or(true, |(first, _)| distance < first) {
+            first = Some((distance, normal));
+        }
+    };
+    for (index, normal) in shape.normals.iter().enumerate(){
+        let approach = direction.dot(*normal);
+        if approach >= 0.0 {
+            continue;
+        }
+        let start = shape.vertices[index] + *normal*shape.radius;
+        let edge = shape.vertices[(index + 1) % shape.vertices.len()] + *normal*shape.radius - start;
+        let distance = (start - origin).dot(*normal)/approach;
+        let along = (origin + direction*distance - start).dot(edge)/edge.length_squared();
+        if (0.0..=1.0).contains(&along) {
+            found(distance, *normal);
+        }
+    }
+    if shape.radius > 0.0 {
+        for vertex in shape.vertices.iter(){
+            let offset = origin - *vertex;
+            let b = offset.dot(direction);
+            let discriminant = b*b - offset.length_squared() + shape.radius*shape.radius;
+            if discriminant >= 0.0 {
+                let distance = -b - discriminant.sqrt();
+                found(distance, (origin + direction*distance - *vertex)/shape.radius);
+            }
+        }
+    }
+    first
+}
+
+//Moves <shape> along <direction> until it touches <other>. Each advance goes up to the plane separating the closest points,
+//which the shapes can't pass without touching, and a shape moving away from that plane never hits.
+//Returns the distance travelled, and the point and outward normal on <other>
+fn cast_convex(shape: &ConvexShape2D, other: &ConvexShape2D, direction: Vector, max_distance: Scalar) -> Option<(Scalar, Vector, Vector)>{
+    let mut distance = 0.0;
+    //the direction between closest points is lost to rounding once the shapes touch, so the normal of the last plane advanced to is kept
+    let mut normal = -direction;
+    for _ in 0..SHAPE_CAST_MAX_ITERATIONS{
+        let closest = surface_distance(&shape.translated(direction*distance), other);
+        if closest.separation <= SHAPE_CAST_TOLERANCE {
+            return Some((distance, closest.point_b, normal));
+        }
+        let approach = direction.dot(closest.normal);
+        if approach <= 0.0 {
+            return None;
+        }
+        normal = -closest.normal;
+        distance += closest.separation/approach;
+        if distance > max_distance {
+            return None;
+        }
+    }
+    None
+}
+
+//Signed distance from <point> to the surface of <shape>, with the closest surface point and the outward normal there
+fn project_convex(shape: &ConvexShape2D, point: Vector) -> (Scalar, Vector, Vector){
+    let closest = gjk_distance(&shape.vertices, &[point]);
+    if closest.distance > 0.0 {
+        let normal = (point - closest.point_a)/closest.distance;
+        return (closest.distance - shape.radius, closest.point_a + normal*shape.radius, normal);
+    }
+    //inside the core, the nearest face is the one the point is least far behind
+    let (separation, normal) = shape.normals.iter().enumerate()
+        .map(|(index, normal)| ((point - shape.vertices[index]).dot(*normal), *normal))
+        .reduce(|nearest, face| if face.0 > nearest.0 { face } else { nearest })
+        .unwrap_or((0.0, Vector::Y));
+    (separation - shape.radius, point + normal*(shape.radius - separation), normal)
+}
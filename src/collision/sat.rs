@@ -0,0 +1,191 @@
+use super::*;
+
+//Separation along the faces of <b> has to beat <a>'s by this much before <b> becomes the reference,
+//so resting contacts don't flip their reference face every other step
+const REFERENCE_RELATIVE_TOLERANCE: Scalar = 0.98;
+const REFERENCE_ABSOLUTE_TOLERANCE: Scalar = 1e-5;
+//rounded shapes whose face separation is this close to their true distance are touching face to face
+const FACE_CONTACT_TOLERANCE: Scalar = 1e-3;
+
+//Feature ids of contact points created by clipping against either side of the reference face
+const CLIPPED_AT_START: u32 = 4;
+const CLIPPED_AT_END: u32 = 5;
+//Feature id of the single point between the closest features of two rounded shapes, above the flip bit of clipped points
+const CLOSEST_POINT: u32 = 1 << 25;
+
+//Convex shape in world space, made of every point within <radius> of the hull of <vertices>.
+//Circles have a single vertex, capsules and polyline segments have two
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ConvexShape2D{
+    //counter-clockwise
+    pub vertices: Vec<Vector>,
+    //outward normal of the edge starting at each vertex, empty for a single vertex
+    pub normals: Vec<Vector>,
+    pub radius: Scalar
+}
+impl ConvexShape2D{
+    pub fn new(mut vertices: Vec<Vector>, radius: Scalar) -> Self{
+        if vertices.len() == 2 && vertices[0].distance_squared(vertices[1]) <= Scalar::EPSILON {
+            vertices.truncate(1);
+        }
+        let normals = if vertices.len() < 2 { Vec::new() } else {
+            (0..vertices.len()).map(|index| {
+                let edge = vertices[(index + 1) % vertices.len()] - vertices[index];
+                Vector::new(edge.y, -edge.x).normalize()
+            }).collect()
+        };
+        Self{vertices, normals, radius}
+    }
+}
+
+//Narrow phase for two BoxCollider2Ds placed at <pose_a> and <pose_b>, by the separating axis test.
+//Returns None when the boxes don't overlap
+pub fn collide_boxes(a: &BoxCollider2D, pose_a: Isometry2D, b: &BoxCollider2D, pose_b: Isometry2D) -> Option<ContactManifold2D>{
+    collide_convex(&box_shape(a, pose_a), &box_shape(b, pose_b))
+}
+
+pub(crate) fn box_shape(collider: &BoxCollider2D, pose: Isometry2D) -> ConvexShape2D{
+    let (center, half) = (collider.center_position, collider.half_extents);
+    let corners = [
+        center + Vector::new(-half.x, -half.y), 
+        center + Vector::new(half.x, -half.y), 
+        center + half, 
+        center + Vector::new(-half.x, half.y)
+    ];
+    ConvexShape2D::new(corners.iter().map(|corner| pose.transform_point(*corner)).collect(), 0.0)
+}
+
+//Contact between any two convex shapes, None when they don't touch.
+//Sharp shapes and overlapping cores use the separating axis test, rounded shapes apart from each other use the distance between their cores
+pub fn collide_convex(a: &ConvexShape2D, b: &ConvexShape2D) -> Option<ContactManifold2D>{
+    let radius = a.radius + b.radius;
+    if radius > 0.0 {
+        let closest = gjk_distance(&a.vertices, &b.vertices);
+        if closest.distance > radius {
+            return None;
+        }
+        if closest.distance > 0.0 {
+            //flat sides resting against each other get two points, as long as the faces are what's closest
+            if let Some((manifold, separation)) = clip_faces(a, b) {
+                if (separation - closest.distance).abs() <= FACE_CONTACT_TOLERANCE {
+                    return Some(manifold);
+                }
+            }
+            let normal = (closest.point_b - closest.point_a)/closest.distance;
+            let surface_a = closest.point_a + normal*a.radius;
+            let surface_b = closest.point_b - normal*b.radius;
+            return Some(ContactManifold2D{normal, points: vec![ContactPoint2D{
+                point: (surface_a + surface_b)/2.0,
+                depth: radius - closest.distance,
+                id: CLOSEST_POINT,
+                normal_impulse: 0.0,
+                tangent_impulse: 0.0
+            }]});
+        }
+        //two circles on the same spot have no direction to separate along
+        if a.normals.is_empty() && b.normals.is_empty() {
+            return Some(ContactManifold2D{normal: Vector::Y, points: vec![ContactPoint2D{
+                point: a.vertices[0],
+                depth: radius,
+                id: CLOSEST_POINT,
+                normal_impulse: 0.0,
+                tangent_impulse: 0.0
+            }]});
+        }
+    }
+    clip_faces(a, b).map(|(manifold, _)| manifold)
+}
+
+//Separating axis test between the cores of two shapes.
+//The face of least penetration becomes the reference face, and the most opposed face of the other shape is clipped against its sides.
+//Also returns the separation between the cores along the reference face
+fn clip_faces(a: &ConvexShape2D, b: &ConvexShape2D) -> Option<(ContactManifold2D, Scalar)>{
+    let radius = a.radius + b.radius;
+    let (separation_a, edge_a) = max_separation(a, b);
+    if separation_a > radius {
+        return None;
+    }
+    let (separation_b, edge_b) = max_separation(b, a);
+    if separation_b > radius {
+        return None;
+    }
+    let flip = match (a.normals.is_empty(), b.normals.is_empty()) {
+        (true, true) => return None,
+        (true, false) => true,
+        (false, true) => false,
+        (false, false) => separation_b > REFERENCE_RELATIVE_TOLERANCE*separation_a + REFERENCE_ABSOLUTE_TOLERANCE
+    };
+    let (reference, incident, edge, separation) = if flip { (b, a, edge_b, separation_b) } else { (a, b, edge_a, separation_a) };
+    let normal = reference.normals[edge];
+    let start = reference.vertices[edge];
+    let end = reference.vertices[(edge + 1) % reference.vertices.len()];
+    let mut points = [(incident.vertices[0], 0); 2];
+    let mut count = 1;
+    let mut incident_edge = 0;
+    if !incident.normals.is_empty() {
+        incident_edge = (0..incident.normals.len())
+            .min_by(|i, j| normal.dot(incident.normals[*i]).total_cmp(&normal.dot(incident.normals[*j])))?;
+        let next = (incident_edge + 1) % incident.vertices.len();
+        points = [(incident.vertices[incident_edge], 0), (incident.vertices[next], 1)];
+        count = 2;
+        //clip the incident edge to the sides of the reference face
+        let tangent = (end - start).normalize();
+        clip_segment(&mut points, &mut count, -tangent, -tangent.dot(start), CLIPPED_AT_START)?;
+        clip_segment(&mut points, &mut count, tangent, tangent.dot(end), CLIPPED_AT_END)?;
+    }
+    let face = normal.dot(start);
+    let points: Vec<ContactPoint2D> = points[..count].iter().filter_map(|(point, feature)| {
+        let separation = normal.dot(*point) - face;
+        //midway between the reference surface and the incident surface
+        let gap = separation - radius;
+        (gap <= 0.0).then(|| ContactPoint2D{
+            point: *point - normal*(incident.radius + gap/2.0),
+            depth: -gap,
+            //a byte per field, so shapes of up to 256 vertices can't mix up their edges
+            id: (flip as u32) << 24 | (edge as u32) << 16 | (incident_edge as u32) << 8 | feature,
+            normal_impulse: 0.0,
+            tangent_impulse: 0.0
+        })
+    }).collect();
+    if points.is_empty() {
+        return None;
+    }
+    Some((ContactManifold2D{normal: if flip { -normal } else { normal }, points}, separation))
+}
+
+//Largest separation of the core of <other> from any face of <shape>, and that face's index
+fn max_separation(shape: &ConvexShape2D, other: &ConvexShape2D) -> (Scalar, usize){
+    (0..shape.normals.len())
+        .map(|face| {
+            let separation = other.vertices.iter()
+                .map(|vertex| shape.normals[face].dot(*vertex - shape.vertices[face]))
+                .fold(Scalar::INFINITY, Scalar::min);
+            (separation, face)
+        })
+        .fold((Scalar::NEG_INFINITY, 0), |best, face| if face.0 > best.0 { face } else { best })
+}
+
+//Cuts the first <count> points back to where dot(<normal>, point) <= <offset>, giving new end points the feature id <feature>.
+//A segment ending on the plane keeps just that end. None when every point lies outside
+fn clip_segment(points: &mut [(Vector, u32); 2], count: &mut usize, normal: Vector, offset: Scalar, feature: u32) -> Option<()>{
+    let distances = points.map(|(point, _)| normal.dot(point) - offset);
+    let mut clipped = *points;
+    let mut kept = 0;
+    for index in 0..*count{
+        if distances[index] <= 0.0 {
+            clipped[kept] = points[index];
+            kept += 1;
+        }
+    }
+    if *count == 2 && distances[0]*distances[1] < 0.0 {
+        let t = distances[0]/(distances[0] - distances[1]);
+        clipped[kept] = (points[0].0 + (points[1].0 - points[0].0)*t, feature);
+        kept += 1;
+    }
+    if kept == 0 {
+        return None;
+    }
+    *points = clipped;
+    *count = kept;
+    Some(())
+}
@@ -0,0 +1,35 @@
+use super::*;
+
+//Convex pieces of <collider> placed at <pose>, one per segment for polylines
+pub fn convex_parts(collider: Collider2D, pose: Isometry2D) -> Vec<ConvexShape2D>{
+    match collider {
+        Collider2D::Box(collider) => vec![box_shape(collider, pose)],
+        Collider2D::Circle(collider) => vec![ConvexShape2D::new(vec![pose.transform_point(collider.center_position)], collider.radius)],
+        Collider2D::Capsule(collider) => {
+            let offset = Vector::Y*collider.half_height;
+            let ends = vec![pose.transform_point(collider.center_position - offset), pose.transform_point(collider.center_position + offset)];
+            vec![ConvexShape2D::new(ends, collider.radius)]
+        },
+        Collider2D::ConvexPolygon(collider) => {
+            vec![ConvexShape2D::new(collider.vertices.iter().map(|vertex| pose.transform_point(*vertex)).collect(), 0.0)]
+        },
+        Collider2D::Polyline(collider) => collider.vertices.windows(2)
+            .map(|segment| ConvexShape2D::new(vec![pose.transform_point(segment[0]), pose.transform_point(segment[1])], 0.0))
+            .collect()
+    }
+}
+
+//Narrow phase between any two colliders, returning a manifold for every pair of convex pieces that touch.
+//Each manifold comes with the index of the piece pair, which is the polyline segment when one of the colliders is a polyline
+pub fn collide_colliders(a: Collider2D, pose_a: Isometry2D, b: Collider2D, pose_b: Isometry2D) -> Vec<(u32, ContactManifold2D)>{
+    let (parts_a, parts_b) = (convex_parts(a, pose_a), convex_parts(b, pose_b));
+    let mut manifolds = Vec::new();
+    for (index_a, part_a) in parts_a.iter().enumerate(){
+        for (index_b, part_b) in parts_b.iter().enumerate(){
+            if let Some(manifold) = collide_convex(part_a, part_b) {
+                manifolds.push(((index_a*parts_b.len() + index_b) as u32, manifold));
+            }
+        }
+    }
+    manifolds
+}
@@ -0,0 +1,199 @@
+use bevy::{ecs::query::Has, utils::{HashMap, HashSet}};
+use crate::simulation::DampedSpring2D;
+use super::*;
+
+//When resting bodies stop being simulated
+#[derive(Resource, Clone, Debug)]
+pub struct SleepConfig{
+    pub enabled: bool,
+    //kinetic energy under which a body counts as resting, in joules
+    pub energy_threshold: Scalar,
+    //seconds every body of an island has to rest before the island falls asleep
+    pub time_to_sleep: Scalar
+}
+impl Default for SleepConfig{
+    fn default() -> Self {
+        Self{enabled: true, energy_threshold: 1e-3, time_to_sleep: 0.5}
+    }
+}
+
+//Groups of dynamic bodies linked by contacts, joints or springs in the latest step, which fall asleep and wake up together.
+//Static colliders, world anchors and bodies that aren't dynamic don't link the bodies that touch them
+#[derive(Resource, Clone, Debug, Default)]
+pub struct SimulationIslands{
+    //each island ordered by entity, islands ordered by their first body
+    islands: Vec<Vec<Entity>>,
+    //island of every body
+    indices: HashMap<Entity, usize>
+}
+impl SimulationIslands{
+    //<bodies> in entity order, <links> pairs of bodies that move each other
+    pub fn new(bodies: &[Entity], links: impl IntoIterator<Item = (Entity, Entity)>) -> Self{
+        let positions: HashMap<Entity, usize> = bodies.iter().enumerate().map(|(index, entity)| (*entity, index)).collect();
+        //union find, with the smaller index as root so the result doesn't depend on the order of the links
+        let mut parents: Vec<usize> = (0..bodies.len()).collect();
+        for (a, b) in links{
+            let (Some(a), Some(b)) = (positions.get(&a), positions.get(&b)) else {
+                continue;
+            };
+            let (a, b) = (root(&mut parents, *a), root(&mut parents, *b));
+            parents[a.max(b)] = a.min(b);
+        }
+        let mut islands: Vec<Vec<Entity>> = Vec::new();
+        let mut roots: HashMap<usize, usize> = HashMap::new();
+        let mut indices = HashMap::new();
+        for (position, entity) in bodies.iter().enumerate(){
+            let index = *roots.entry(root(&mut parents, position)).or_insert_with(|| {
+                islands.push(Vec::new());
+                islands.len() - 1
+            });
+            islands[index].push(*entity);
+            indices.insert(*entity, index);
+        }
+        Self{islands, indices}
+    }
+    pub fn islands(&self) -> &[Vec<Entity>]{
+        &self.islands
+    }
+    pub fn island(&self, body: Entity) -> Option<&[Entity]>{
+        self.indices.get(&body).map(|index| self.islands[*index].as_slice())
+    }
+}
+
+fn root(parents: &mut [usize], mut index: usize) -> usize{
+    while parents[index] != index {
+        parents[index] = parents[parents[index]];
+        index = parents[index];
+    }
+    index
+}
+
+//Bodies of every joint, kept by wake_bodies to find the bodies of joints once they are removed
+#[derive(Resource, Clone, Debug, Default)]
+pub struct JointBodies2D(HashMap<Entity, [Option<Entity>; 2]>);
+
+//Kinetic energy compared against SleepConfig::energy_threshold.
+//Axes of infinite mass count with unit mass, so immovable bodies given a velocity stay awake
+fn resting_energy(data: &SimulationData, body: &RigidBody2D) -> Scalar{
+    let mass = if body.mass > 0.0 { body.mass } else { 1.0 };
+    let inertia = if body.rotational_inertia > 0.0 { body.rotational_inertia } else { 1.0 };
+    0.5*mass*data.state.velocity.length_squared() + 0.5*inertia*data.state.angular_velocity*data.state.angular_velocity
+}
+
+//Wakes sleeping bodies given an impulse or force, and the bodies of joints added, changed or removed since the last step.
+//Runs before the solver so woken bodies are stepped right away
+pub fn wake_bodies(
+    mut commands: Commands,
+    sleeping: Query<(Entity, &SimulationData), With<Sleeping>>,
+    changed_joints: Query<(Entity, &Joint2D), Changed<Joint2D>>,
+    mut removed_joints: RemovedComponents<Joint2D>,
+    mut jointed: ResMut<JointBodies2D>
+){
+    let mut woken: Vec<Entity> = sleeping.iter()
+        .filter(|(_, data)| data.linear_impulse != Vector::ZERO || data.angular_impulse != 0.0 || data.force != Vector::ZERO || data.torque != 0.0)
+        .map(|(entity, _)| entity)
+        .collect();
+    for joint in removed_joints.iter(){
+        woken.extend(jointed.0.remove(&joint).into_iter().flatten().flatten());
+    }
+    for (entity, joint) in changed_joints.iter(){
+        jointed.0.insert(entity, [Some(joint.body_a), joint.body_b]);
+        woken.extend([Some(joint.body_a), joint.body_b].into_iter().flatten());
+    }
+    woken.sort_unstable();
+    woken.dedup();
+    for entity in woken.into_iter().filter(|entity| sleeping.contains(*entity)){
+        commands.entity(entity).remove::<Sleeping>();
+    }
+}
+
+//Wakes the island of every sleeping body touched by an awake dynamic one, which the solver would otherwise treat as immovable.
+//Runs after the contacts are found and before the solver, as woken bodies are solved in the same step
+pub fn wake_touched_islands(
+    mut commands: Commands,
+    contacts: Res<Contacts2D>,
+    islands: Res<SimulationIslands>,
+    bodies: Query<(&RigidBody2D, Has<Sleeping>), With<SimulationData>>
+){
+    let awake = |body: Option<Entity>| body.and_then(|body| bodies.get(body).ok()).is_some_and(|(body, sleeping)| body.is_dynamic() && !sleeping);
+    let sleeping = |body: Option<Entity>| body.filter(|body| bodies.get(*body).is_ok_and(|(_, sleeping)| sleeping));
+    let mut woken: Vec<Entity> = contacts.contacts.iter()
+        .filter(|contact| !contact.sensor)
+        .flat_map(|contact| [(contact.body_a, contact.body_b), (contact.body_b, contact.body_a)])
+        .filter(|(body, _)| awake(*body))
+        .filter_map(|(_, other)| sleeping(other))
+        .flat_map(|body| islands.island(body).map_or(vec![body], <[Entity]>::to_vec))
+        .collect();
+    woken.sort_unstable();
+    woken.dedup();
+    for entity in woken.into_iter().filter(|entity| bodies.get(*entity).is_ok_and(|(_, sleeping)| sleeping)){
+        commands.entity(entity).remove::<Sleeping>();
+    }
+}
+
+//Finds the islands of the step and puts every island whose bodies have all rested for SleepConfig::time_to_sleep to sleep.
+//Any other island is woken as a whole, so a moving body wakes everything it touches, as are bodies whose colliders stopped touching something
+//and bodies linked to a moving kinematic body. Only dynamic bodies sleep
+#[allow(clippy::too_many_arguments)]
+pub fn update_sleeping(
+    mut commands: Commands,
+    config: Res<SleepConfig>,
+    timestep: Res<FlatlandTimestep>,
+    contacts: Res<Contacts2D>,
+    broad_phase: Res<BroadPhase2D>,
+    joints: Query<&Joint2D>,
+    springs: Query<&DampedSpring2D>,
+    mut ended: EventReader<CollisionEnded>,
+    mut islands: ResMut<SimulationIslands>,
+    mut bodies: Query<(Entity, &mut SimulationData, &RigidBody2D, Has<Sleeping>)>
+){
+    let mut entities = Vec::new();
+    for (entity, mut data, body, sleeping) in bodies.iter_mut(){
+        if !body.is_dynamic() {
+            if sleeping {
+                commands.entity(entity).remove::<Sleeping>();
+            }
+            continue;
+        }
+        entities.push(entity);
+        if !sleeping {
+            data.idle_time = if resting_energy(&data, body) <= config.energy_threshold { data.idle_time + timestep.step } else { 0.0 };
+        }
+    }
+    entities.sort_unstable();
+    let links: Vec<(Option<Entity>, Option<Entity>)> = contacts.contacts.iter()
+        .filter(|contact| !contact.sensor)
+        .map(|contact| (contact.body_a, contact.body_b))
+        .chain(joints.iter().map(|joint| (Some(joint.body_a), joint.body_b)))
+        .chain(springs.iter().map(|spring| (Some(spring.body_a), spring.body_b)))
+        .collect();
+    *islands = SimulationIslands::new(&entities, links.iter().filter_map(|(a, b)| a.zip(*b)));
+
+    let moving_kinematic = |body: Option<Entity>| body.and_then(|body| bodies.get(body).ok())
+        .is_some_and(|(_, data, body, _)| body.is_kinematic() && (data.state.velocity != Vector::ZERO || data.state.angular_velocity != 0.0));
+    //a body that lost a contact may have lost its support
+    let disturbed: HashSet<Entity> = ended.iter()
+        .flat_map(|pair| [pair.a, pair.b])
+        .filter_map(|collider| broad_phase.proxy(collider).and_then(|proxy| proxy.body))
+        .chain(links.iter()
+            .flat_map(|(a, b)| [(*a, *b), (*b, *a)])
+            .filter(|(_, other)| moving_kinematic(*other))
+            .filter_map(|(body, _)| body))
+        .collect();
+    for island in islands.islands(){
+        let rested = config.enabled && island.iter().all(|entity| !disturbed.contains(entity) && bodies.get(*entity)
+            .is_ok_and(|(_, data, _, sleeping)| sleeping || data.idle_time >= config.time_to_sleep));
+        for entity in island{
+            let Ok((_, mut data, _, sleeping)) = bodies.get_mut(*entity) else {continue};
+            if rested && !sleeping {
+                data.state.velocity = Vector::ZERO;
+                data.state.angular_velocity = 0.0;
+                data.previous = data.state;
+                data.idle_time = 0.0;
+                commands.entity(*entity).insert(Sleeping);
+            }else if !rested && sleeping {
+                commands.entity(*entity).remove::<Sleeping>();
+            }
+        }
+    }
+}
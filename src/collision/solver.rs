@@ -0,0 +1,278 @@
+use bevy::{ecs::query::Has, utils::HashMap};
+use super::*;
+
+//How the material values of two colliders in contact combine into the value used for the contact
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum CombineRule{
+    #[default]
+    Average,
+    Min,
+    Max,
+    Multiply,
+    GeometricMean
+}
+impl CombineRule{
+    pub fn combine(&self, a: Scalar, b: Scalar) -> Scalar{
+        match self {
+            Self::Average => (a + b)/2.0,
+            Self::Min => a.min(b),
+            Self::Max => a.max(b),
+            Self::Multiply => a*b,
+            Self::GeometricMean => (a*b).max(0.0).sqrt()
+        }
+    }
+}
+
+//Settings of the sequential impulse contact solver
+#[derive(Resource, Clone, Debug)]
+pub struct ContactSolverConfig{
+    pub velocity_iterations: usize,
+    pub position_iterations: usize,
+    //start every step from the impulses the same contact points needed in the last one, which lets stacks come to rest
+    pub warm_starting: bool,
+    pub restitution_combine: CombineRule,
+    pub friction_combine: CombineRule,
+    //closing speeds below this don't bounce, so resting contacts settle instead of jittering
+    pub restitution_threshold: Scalar,
+    //penetration left uncorrected, so touching colliders keep their contacts from one step to the next
+    pub allowed_penetration: Scalar,
+    //fraction of the remaining penetration removed by each position iteration
+    pub position_correction: Scalar,
+    //largest distance a single position iteration may move a contact
+    pub max_position_correction: Scalar
+}
+impl Default for ContactSolverConfig{
+    fn default() -> Self {
+        Self{
+            velocity_iterations: 8,
+            position_iterations: 4,
+            warm_starting: true,
+            restitution_combine: CombineRule::Max,
+            friction_combine: CombineRule::GeometricMean,
+            restitution_threshold: 1.0,
+            allowed_penetration: 0.005,
+            position_correction: 0.5,
+            max_position_correction: 0.2
+        }
+    }
+}
+
+//Velocity and accumulated position change of one body taking part in the solve
+#[derive(Clone, Copy, Debug, Default)]
+pub(super) struct SolverBody2D{
+    pub center: Vector,
+    pub angle: Scalar,
+    //in the body's local frame, relative to its transform origin
+    pub center_of_mass: Vector,
+    pub velocity: Vector,
+    pub angular_velocity: Scalar,
+    pub inverse_mass: Scalar,
+    pub inverse_inertia: Scalar,
+    pub translation: Vector,
+    pub rotation: Scalar
+}
+impl SolverBody2D{
+    pub fn velocity_at(&self, arm: Vector) -> Vector{
+        self.velocity + arm.perp()*self.angular_velocity
+    }
+    pub fn apply_impulse(&mut self, arm: Vector, impulse: Vector){
+        self.velocity += impulse*self.inverse_mass;
+        self.angular_velocity += arm.perp_dot(impulse)*self.inverse_inertia;
+    }
+    pub fn apply_position_impulse(&mut self, arm: Vector, impulse: Vector){
+        self.translation += impulse*self.inverse_mass;
+        self.rotation += arm.perp_dot(impulse)*self.inverse_inertia;
+    }
+}
+
+//One contact point prepared for the solve, with its lever arms from each body's centre of mass
+#[derive(Clone, Copy, Debug)]
+struct SolverPoint2D{
+    arm_a: Vector,
+    arm_b: Vector,
+    normal_mass: Scalar,
+    tangent_mass: Scalar,
+    //closing speed the normal impulse aims for, from restitution
+    target_speed: Scalar,
+    depth: Scalar
+}
+
+//Resolves every contact found this step and every Joint2D by sequential impulses, 
+//writing the corrected velocities and positions into SimulationData.
+//Static colliders, world anchors, sleeping bodies and bodies that aren't dynamic act as bodies of infinite mass,
+//kinematic ones moving at their own velocity. A joint's body_b without SimulationData is a static anchor placed by its GlobalTransform.
+//The impulses kept for warm starting are written past change detection, so Changed<Joint2D> only sees changes made outside the solver
+pub fn solve_constraints(
+    config: Res<ContactSolverConfig>,
+    timestep: Res<FlatlandTimestep>,
+    mut contacts: ResMut<Contacts2D>,
+    mut joints: Query<(Entity, &mut Joint2D)>,
+    mut bodies: Query<(&mut SimulationData, &RigidBody2D, Has<Sleeping>)>,
+    anchors: Query<&GlobalTransform>
+){
+    let movable = |body: Option<Entity>| body.is_some_and(|body| bodies.get(body).is_ok_and(|(_, body, sleeping)| body.is_dynamic() && !sleeping));
+    //joints between bodies that can't move are left alone
+    let mut joints: Vec<(Entity, Mut<Joint2D>)> = joints.iter_mut()
+        .filter(|(_, joint)| bodies.contains(joint.body_a) && joint.body_b.map_or(true, |body| bodies.contains(body) || anchors.contains(body)))
+        .filter(|(_, joint)| movable(Some(joint.body_a)) || movable(joint.body_b))
+        .collect();
+    if contacts.contacts.is_empty() && joints.is_empty() {
+        return;
+    }
+    joints.sort_unstable_by_key(|(entity, _)| *entity);
+    //solver bodies in the order they first appear in the sorted contact list, index 0 is the shared static body
+    let mut indices: HashMap<Entity, usize> = HashMap::new();
+    let mut solver_bodies = vec![SolverBody2D::default()];
+    let mut index_of = |entity: Option<Entity>, solver_bodies: &mut Vec<SolverBody2D>| -> usize {
+        let Some(entity) = entity else {return 0};
+        *indices.entry(entity).or_insert_with(|| {
+            let Ok((data, body, sleeping)) = bodies.get(entity) else {
+                //a joint anchored to an entity that isn't a body, which holds still wherever its transform puts it
+                let Ok(anchor) = anchors.get(entity) else {return 0};
+                let pose = Isometry2D::from_transform(&anchor.compute_transform());
+                solver_bodies.push(SolverBody2D{center: pose.translation, angle: pose.angle, ..Default::default()});
+                return solver_bodies.len() - 1;
+            };
+            let (inverse_mass, inverse_inertia) = if sleeping { (0.0, 0.0) } else { (body.inverse_mass(), body.inverse_rotational_inertia()) };
+            solver_bodies.push(SolverBody2D{
+                center: data.state.position,
+                angle: data.state.angle,
+                center_of_mass: body.center_of_mass,
+                velocity: data.state.velocity,
+                angular_velocity: data.state.angular_velocity,
+                inverse_mass,
+                inverse_inertia,
+                ..Default::default()
+            });
+            solver_bodies.len() - 1
+        })
+    };
+    //sensor contacts and contacts where neither body can move get no solver points, which leaves them out of every iteration
+    //and keeps their impulses for when the bodies wake up
+    let pairs: Vec<(usize, usize)> = contacts.contacts.iter()
+        .map(|contact| if contact.sensor || !(movable(contact.body_a) || movable(contact.body_b)) { (0, 0) } else {
+            (index_of(contact.body_a, &mut solver_bodies), index_of(contact.body_b, &mut solver_bodies))
+        })
+        .collect();
+    let points: Vec<Vec<SolverPoint2D>> = contacts.contacts.iter().zip(pairs.iter()).map(|(contact, (a, b))| {
+        if (*a, *b) == (0, 0) {
+            return Vec::new();
+        }
+        let (a, b) = (&solver_bodies[*a], &solver_bodies[*b]);
+        let normal = contact.manifold.normal;
+        let tangent = normal.perp();
+        contact.manifold.points.iter().map(|point| {
+            let (arm_a, arm_b) = (point.point - a.center, point.point - b.center);
+            let effective_mass = |direction: Vector| {
+                let k = a.inverse_mass + b.inverse_mass 
+                    + a.inverse_inertia*arm_a.perp_dot(direction).powi(2) 
+                    + b.inverse_inertia*arm_b.perp_dot(direction).powi(2);
+                if k > 0.0 { k.recip() } else { 0.0 }
+            };
+            let closing_speed = (b.velocity_at(arm_b) - a.velocity_at(arm_a)).dot(normal);
+            SolverPoint2D{
+                arm_a, 
+                arm_b,
+                normal_mass: effective_mass(normal),
+                tangent_mass: effective_mass(tangent),
+                target_speed: if closing_speed < -config.restitution_threshold { -contact.restitution*closing_speed } else { 0.0 },
+                depth: point.depth
+            }
+        }).collect()
+    }).collect();
+    let solver_joints: Vec<SolverJoint2D> = joints.iter().map(|(_, joint)| {
+        let (a, b) = (index_of(joint.body_b, &mut solver_bodies), index_of(Some(joint.body_a), &mut solver_bodies));
+        SolverJoint2D::new(joint, (a, &solver_bodies[a]), (b, &solver_bodies[b]), timestep.step)
+    }).collect();
+    for ((_, joint), prepared) in joints.iter_mut().zip(solver_joints.iter()){
+        let (mut body_a, mut body_b) = (solver_bodies[prepared.a], solver_bodies[prepared.b]);
+        prepared.warm_start(joint.bypass_change_detection(), &mut body_a, &mut body_b, config.warm_starting);
+        store(&mut solver_bodies, (prepared.a, body_a), (prepared.b, body_b));
+    }
+    for (contact, ((a, b), points)) in contacts.contacts.iter_mut().zip(pairs.iter().zip(points.iter())){
+        let (mut body_a, mut body_b) = (solver_bodies[*a], solver_bodies[*b]);
+        let (normal, tangent) = (contact.manifold.normal, contact.manifold.normal.perp());
+        for (point, prepared) in contact.manifold.points.iter_mut().zip(points.iter()){
+            if !config.warm_starting {
+                point.normal_impulse = 0.0;
+                point.tangent_impulse = 0.0;
+            }
+            let impulse = normal*point.normal_impulse + tangent*point.tangent_impulse;
+            body_a.apply_impulse(prepared.arm_a, -impulse);
+            body_b.apply_impulse(prepared.arm_b, impulse);
+        }
+        store(&mut solver_bodies, (*a, body_a), (*b, body_b));
+    }
+    for _ in 0..config.velocity_iterations{
+        for ((_, joint), prepared) in joints.iter_mut().zip(solver_joints.iter()){
+            let (mut body_a, mut body_b) = (solver_bodies[prepared.a], solver_bodies[prepared.b]);
+            prepared.solve_velocity(joint.bypass_change_detection(), &mut body_a, &mut body_b);
+            store(&mut solver_bodies, (prepared.a, body_a), (prepared.b, body_b));
+        }
+        for (contact, ((a, b), points)) in contacts.contacts.iter_mut().zip(pairs.iter().zip(points.iter())){
+            let (mut body_a, mut body_b) = (solver_bodies[*a], solver_bodies[*b]);
+            let (normal, tangent) = (contact.manifold.normal, contact.manifold.normal.perp());
+            //friction first, so the normal impulses that end the iteration are the ones keeping the bodies apart
+            for (point, prepared) in contact.manifold.points.iter_mut().zip(points.iter()){
+                let relative = body_b.velocity_at(prepared.arm_b) - body_a.velocity_at(prepared.arm_a);
+                let limit = contact.friction*point.normal_impulse;
+                let accumulated = (point.tangent_impulse - prepared.tangent_mass*relative.dot(tangent)).clamp(-limit, limit);
+                let impulse = tangent*(accumulated - point.tangent_impulse);
+                point.tangent_impulse = accumulated;
+                body_a.apply_impulse(prepared.arm_a, -impulse);
+                body_b.apply_impulse(prepared.arm_b, impulse);
+            }
+            for (point, prepared) in contact.manifold.points.iter_mut().zip(points.iter()){
+                let relative = body_b.velocity_at(prepared.arm_b) - body_a.velocity_at(prepared.arm_a);
+                let accumulated = (point.normal_impulse - prepared.normal_mass*(relative.dot(normal) - prepared.target_speed)).max(0.0);
+                let impulse = normal*(accumulated - point.normal_impulse);
+                point.normal_impulse = accumulated;
+                body_a.apply_impulse(prepared.arm_a, -impulse);
+                body_b.apply_impulse(prepared.arm_b, impulse);
+            }
+            store(&mut solver_bodies, (*a, body_a), (*b, body_b));
+        }
+    }
+    //push penetrating bodies apart, using the separation predicted from the corrections made so far
+    for _ in 0..config.position_iterations{
+        for prepared in solver_joints.iter(){
+            let (mut body_a, mut body_b) = (solver_bodies[prepared.a], solver_bodies[prepared.b]);
+            prepared.solve_position(&config, &mut body_a, &mut body_b);
+            store(&mut solver_bodies, (prepared.a, body_a), (prepared.b, body_b));
+        }
+        for (contact, ((a, b), points)) in contacts.contacts.iter().zip(pairs.iter().zip(points.iter())){
+            let (mut body_a, mut body_b) = (solver_bodies[*a], solver_bodies[*b]);
+            let normal = contact.manifold.normal;
+            for prepared in points.iter(){
+                let moved = (body_b.translation + prepared.arm_b.perp()*body_b.rotation) - (body_a.translation + prepared.arm_a.perp()*body_a.rotation);
+                let separation = moved.dot(normal) - prepared.depth;
+                let correction = (config.position_correction*(separation + config.allowed_penetration)).clamp(-config.max_position_correction, 0.0);
+                let impulse = normal*(-prepared.normal_mass*correction);
+                body_a.apply_position_impulse(prepared.arm_a, -impulse);
+                body_b.apply_position_impulse(prepared.arm_b, impulse);
+            }
+            store(&mut solver_bodies, (*a, body_a), (*b, body_b));
+        }
+    }
+    for (entity, index) in indices{
+        let Ok((mut data, body, false)) = bodies.get_mut(entity) else {continue};
+        if !body.is_dynamic() {
+            continue;
+        }
+        let body = &solver_bodies[index];
+        data.state.velocity = body.velocity;
+        data.state.angular_velocity = body.angular_velocity;
+        data.state.position += body.translation;
+        data.state.angle += body.rotation;
+    }
+}
+
+//Writes back the two bodies of a contact or joint, leaving the shared static body untouched
+fn store(solver_bodies: &mut [SolverBody2D], (a, body_a): (usize, SolverBody2D), (b, body_b): (usize, SolverBody2D)){
+    if a != 0 {
+        solver_bodies[a] = body_a;
+    }
+    if b != 0 {
+        solver_bodies[b] = body_b;
+    }
+}
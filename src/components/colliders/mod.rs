@@ -1,9 +1,282 @@
-use bevy::prelude::*;
+use std::{iter::Sum, ops::Add};
+use bevy::{prelude::*, ecs::query::AnyOf, utils::HashSet};
+use crate::scalar::*;
 
 #[derive(Component)]
 pub struct BoxCollider2D{
-    pub center_position: Vec2,
-    pub half_extents: Vec2,
-    pub restitution: f32,
-    pub friction: f32
-}
\ No newline at end of file
+    pub center_position: Vector,
+    pub half_extents: Vector,
+    pub restitution: Scalar,
+    pub friction: Scalar
+}
+
+#[derive(Component)]
+pub struct CircleCollider2D{
+    pub center_position: Vector,
+    pub radius: Scalar,
+    pub restitution: Scalar,
+    pub friction: Scalar
+}
+
+//Rounded segment running along the local y axis, <half_height> either side of <center_position>
+#[derive(Component)]
+pub struct CapsuleCollider2D{
+    pub center_position: Vector,
+    pub half_height: Scalar,
+    pub radius: Scalar,
+    pub restitution: Scalar,
+    pub friction: Scalar
+}
+
+//<vertices> are in the entity's local frame and have to wind counter-clockwise around a convex outline,
+//use ConvexPolygonCollider2D::from_points to build one from any set of points
+#[derive(Component)]
+pub struct ConvexPolygonCollider2D{
+    pub vertices: Vec<Vector>,
+    pub restitution: Scalar,
+    pub friction: Scalar
+}
+impl ConvexPolygonCollider2D{
+    //Convex hull of <points>, None when they don't enclose any area
+    pub fn from_points(points: &[Vector], restitution: Scalar, friction: Scalar) -> Option<Self>{
+        let mut sorted = points.to_vec();
+        sorted.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+        //Andrew's monotone chain, building the lower and then the upper hull
+        let mut hull: Vec<Vector> = Vec::with_capacity(sorted.len() + 1);
+        for pass in [sorted.clone(), sorted.into_iter().rev().collect()]{
+            let start = hull.len();
+            for point in pass{
+                while hull.len() >= start + 2 && (hull[hull.len() - 1] - hull[hull.len() - 2]).perp_dot(point - hull[hull.len() - 1]) <= 0.0 {
+                    hull.pop();
+                }
+                hull.push(point);
+            }
+            hull.pop();
+        }
+        (hull.len() >= 3).then_some(Self{vertices: hull, restitution, friction})
+    }
+}
+
+//Chain of segments through <vertices>, for static terrain such as sloped ground.
+//Polylines have no mass and are never tested against each other
+#[derive(Component)]
+pub struct PolylineCollider2D{
+    pub vertices: Vec<Vector>,
+    pub restitution: Scalar,
+    pub friction: Scalar
+}
+
+//Collision groups of a collider. Two colliders interact only if each is a member of a group the other one filters for.
+//Colliders without this component are members of every group and interact with every group
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CollisionLayers2D{
+    pub memberships: u32,
+    pub filters: u32
+}
+impl CollisionLayers2D{
+    pub const ALL: Self = Self{memberships: u32::MAX, filters: u32::MAX};
+    pub const NONE: Self = Self{memberships: 0, filters: 0};
+
+    pub fn new(memberships: u32, filters: u32) -> Self{
+        Self{memberships, filters}
+    }
+    pub fn interacts_with(&self, other: &CollisionLayers2D) -> bool{
+        self.memberships & other.filters != 0 && other.memberships & self.filters != 0
+    }
+}
+impl Default for CollisionLayers2D{
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+//Makes the collider on this entity report overlaps through contacts and collision events, without pushing anything
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct Sensor2D;
+
+//Colliders touching the collider on this entity, kept up to date by the simulation after every step
+#[derive(Component, Clone, Debug, Default)]
+pub struct CollidingEntities(pub HashSet<Entity>);
+
+//Every collider component of an entity, at most one of which is expected to be present
+pub type ColliderComponents = AnyOf<(
+    &'static BoxCollider2D, 
+    &'static CircleCollider2D, 
+    &'static CapsuleCollider2D, 
+    &'static ConvexPolygonCollider2D, 
+    &'static PolylineCollider2D
+)>;
+pub type WithCollider = Or<(
+    With<BoxCollider2D>, 
+    With<CircleCollider2D>, 
+    With<CapsuleCollider2D>, 
+    With<ConvexPolygonCollider2D>, 
+    With<PolylineCollider2D>
+)>;
+pub type ChangedCollider = Or<(
+    Changed<BoxCollider2D>, 
+    Changed<CircleCollider2D>, 
+    Changed<CapsuleCollider2D>, 
+    Changed<ConvexPolygonCollider2D>, 
+    Changed<PolylineCollider2D>
+)>;
+
+//Borrowed view of whichever collider component an entity has
+#[derive(Clone, Copy)]
+pub enum Collider2D<'a>{
+    Box(&'a BoxCollider2D),
+    Circle(&'a CircleCollider2D),
+    Capsule(&'a CapsuleCollider2D),
+    ConvexPolygon(&'a ConvexPolygonCollider2D),
+    Polyline(&'a PolylineCollider2D)
+}
+impl<'a> Collider2D<'a>{
+    #[allow(clippy::type_complexity)]
+    pub fn from_components(components: (
+        Option<&'a BoxCollider2D>, 
+        Option<&'a CircleCollider2D>, 
+        Option<&'a CapsuleCollider2D>, 
+        Option<&'a ConvexPolygonCollider2D>, 
+        Option<&'a PolylineCollider2D>
+    )) -> Self{
+        match components {
+            (Some(collider), ..) => Self::Box(collider),
+            (_, Some(collider), ..) => Self::Circle(collider),
+            (_, _, Some(collider), ..) => Self::Capsule(collider),
+            (_, _, _, Some(collider), _) => Self::ConvexPolygon(collider),
+            (_, _, _, _, Some(collider)) => Self::Polyline(collider),
+            (None, None, None, None, None) => unreachable!("AnyOf matches entities with at least one collider")
+        }
+    }
+    pub fn restitution(&self) -> Scalar{
+        match self {
+            Self::Box(collider) => collider.restitution,
+            Self::Circle(collider) => collider.restitution,
+            Self::Capsule(collider) => collider.restitution,
+            Self::ConvexPolygon(collider) => collider.restitution,
+            Self::Polyline(collider) => collider.restitution
+        }
+    }
+    pub fn friction(&self) -> Scalar{
+        match self {
+            Self::Box(collider) => collider.friction,
+            Self::Circle(collider) => collider.friction,
+            Self::Capsule(collider) => collider.friction,
+            Self::ConvexPolygon(collider) => collider.friction,
+            Self::Polyline(collider) => collider.friction
+        }
+    }
+}
+
+/*
+    MASS PROPERTIES
+*/
+
+//Density of colliders without a ColliderDensity2D, in mass per square unit
+pub const DEFAULT_COLLIDER_DENSITY: Scalar = 1.0;
+
+//Density of a collider, used to derive the mass of the body it belongs to
+#[derive(Component, Clone, Copy, Debug)]
+pub struct ColliderDensity2D(pub Scalar);
+
+//Mass, centre of mass and moment of inertia about the centre of mass
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MassProperties2D{
+    pub mass: Scalar,
+    pub center: Vector,
+    pub inertia: Scalar
+}
+impl MassProperties2D{
+    //Moment of inertia about <point>, by the parallel axis theorem
+    pub fn inertia_about(&self, point: Vector) -> Scalar{
+        self.inertia + self.mass*self.center.distance_squared(point)
+    }
+    //Moves the shape into a parent frame where its own frame sits at <translation>, rotated by <angle>
+    pub fn transformed(self, translation: Vector, angle: Scalar) -> Self{
+        Self{center: translation + Vector::from_angle(angle).rotate(self.center), ..self}
+    }
+}
+impl Add for MassProperties2D{
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self{
+        let mass = self.mass + rhs.mass;
+        if mass <= 0.0 {
+            return Self{mass, center: (self.center + rhs.center)/2.0, inertia: 0.0};
+        }
+        let center = (self.center*self.mass + rhs.center*rhs.mass)/mass;
+        Self{mass, center, inertia: self.inertia_about(center) + rhs.inertia_about(center)}
+    }
+}
+impl Sum for MassProperties2D{
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self{
+        iter.fold(Self::default(), |total, part| total + part)
+    }
+}
+
+//Collider shapes that contribute to the mass of their body
+pub trait ColliderMassProperties2D{
+    //Mass properties of the shape in the collider's local frame
+    fn mass_properties(&self, density: Scalar) -> MassProperties2D;
+}
+impl ColliderMassProperties2D for Collider2D<'_>{
+    fn mass_properties(&self, density: Scalar) -> MassProperties2D{
+        match self {
+            Self::Box(collider) => collider.mass_properties(density),
+            Self::Circle(collider) => collider.mass_properties(density),
+            Self::Capsule(collider) => collider.mass_properties(density),
+            Self::ConvexPolygon(collider) => collider.mass_properties(density),
+            Self::Polyline(collider) => collider.mass_properties(density)
+        }
+    }
+}
+impl ColliderMassProperties2D for BoxCollider2D{
+    fn mass_properties(&self, density: Scalar) -> MassProperties2D{
+        let size = self.half_extents*2.0;
+        let mass = density*size.x*size.y;
+        MassProperties2D{mass, center: self.center_position, inertia: mass*size.length_squared()/12.0}
+    }
+}
+impl ColliderMassProperties2D for CircleCollider2D{
+    fn mass_properties(&self, density: Scalar) -> MassProperties2D{
+        let mass = density*PI*self.radius*self.radius;
+        MassProperties2D{mass, center: self.center_position, inertia: mass*self.radius*self.radius/2.0}
+    }
+}
+impl ColliderMassProperties2D for CapsuleCollider2D{
+    fn mass_properties(&self, density: Scalar) -> MassProperties2D{
+        let (radius, half_height) = (self.radius, self.half_height);
+        let rectangle = density*4.0*radius*half_height;
+        let caps = density*PI*radius*radius;
+        //each half disc has its centroid this far from its flat side
+        let offset = 4.0*radius/(3.0*PI);
+        let inertia = rectangle*(4.0*radius*radius + 4.0*half_height*half_height)/12.0 
+            + caps*(radius*radius/2.0 + half_height*half_height + 2.0*half_height*offset);
+        MassProperties2D{mass: rectangle + caps, center: self.center_position, inertia}
+    }
+}
+impl ColliderMassProperties2D for ConvexPolygonCollider2D{
+    fn mass_properties(&self, density: Scalar) -> MassProperties2D{
+        //sum over the triangles fanning out from the first vertex
+        let Some(origin) = self.vertices.first().copied() else {return MassProperties2D::default()};
+        let (mut area, mut center, mut inertia) = (0.0, Vector::ZERO, 0.0);
+        for pair in self.vertices[1..].windows(2){
+            let (e1, e2) = (pair[0] - origin, pair[1] - origin);
+            let cross = e1.perp_dot(e2);
+            area += cross/2.0;
+            center += (e1 + e2)*(cross/6.0);
+            inertia += cross/12.0*(e1.length_squared() + e1.dot(e2) + e2.length_squared());
+        }
+        if area <= 0.0 {
+            return MassProperties2D::default();
+        }
+        center /= area;
+        let mass = density*area;
+        //the fan gives inertia about the first vertex, shifted to the centroid
+        MassProperties2D{mass, center: origin + center, inertia: density*inertia - mass*center.length_squared()}
+    }
+}
+impl ColliderMassProperties2D for PolylineCollider2D{
+    fn mass_properties(&self, _: Scalar) -> MassProperties2D{
+        MassProperties2D::default()
+    }
+}
@@ -3,14 +3,69 @@ mod colliders;
 use bevy::{prelude::*, utils::{HashSet, hashbrown::HashMap}};
 use nalgebra::Matrix3;
 pub use colliders::*;
+use super::scalar::*;
 
-#[derive(Component)]
+//How a RigidBody2D moves. Static and kinematic bodies act as infinitely heavy, whatever their mass and inertia,
+//so forces, impulses and contacts don't move them while they push dynamic bodies out of the way
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RigidBodyType2D{
+    //moved by forces, impulses, contacts and joints
+    #[default]
+    Dynamic,
+    //holds still
+    Static,
+    //moved by gameplay code to the pose given to SimulationData::set_kinematic_target, 
+    //with the velocity that gets it there over a step, and holds still without one
+    KinematicPositionBased,
+    //moves at the velocity gameplay code sets on SimulationData::state
+    KinematicVelocityBased
+}
+
+#[derive(Component, Clone, Debug)]
 pub struct RigidBody2D{
-    pub mass: f32,
-    pub rotational_inertia: f32
+    pub mass: Scalar,
+    //about the centre of mass
+    pub rotational_inertia: Scalar,
+    //relative to the transform origin, in the body's local frame. SimulationData tracks this point rather than the origin
+    pub center_of_mass: Vector,
+    pub body_type: RigidBodyType2D
+}
+impl RigidBody2D{
+    //Dynamic body with its centre of mass at the transform origin
+    pub fn new(mass: Scalar, rotational_inertia: Scalar) -> Self{
+        Self{mass, rotational_inertia, center_of_mass: Vector::ZERO, body_type: RigidBodyType2D::Dynamic}
+    }
+    pub fn with_body_type(mut self, body_type: RigidBodyType2D) -> Self{
+        self.body_type = body_type;
+        self
+    }
+    pub fn is_dynamic(&self) -> bool{
+        self.body_type == RigidBodyType2D::Dynamic
+    }
+    pub fn is_kinematic(&self) -> bool{
+        matches!(self.body_type, RigidBodyType2D::KinematicPositionBased | RigidBodyType2D::KinematicVelocityBased)
+    }
+    //Bodies with zero mass or inertia are treated as immovable along that axis, bodies that aren't dynamic along both
+    pub fn inverse_mass(&self) -> Scalar{
+        if self.mass > 0.0 && self.is_dynamic() { self.mass.recip() } else { 0.0 }
+    }
+    pub fn inverse_rotational_inertia(&self) -> Scalar{
+        if self.rotational_inertia > 0.0 && self.is_dynamic() { self.rotational_inertia.recip() } else { 0.0 }
+    }
 }
 
-#[derive(Component)]
+//Opts a RigidBody2D into continuous collision detection, so it can't pass through colliders thinner than the distance it moves in one step
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct ContinuousCollision2D;
+
+//Marks a RigidBody2D that rested long enough to stop being simulated, added and removed by the simulation as its island falls asleep and wakes.
+//Sleeping bodies hold still like static colliders until a contact, impulse, force or joint change wakes them. Remove it to wake a body by hand
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct Sleeping;
+
+//Moment of inertia each collider of a compound body contributes about the body's centre of mass, keyed by the collider's Entity::index.
+//Adding it to a RigidBody2D makes update_mass_properties derive the body's mass, centre of mass and inertia from its colliders
+#[derive(Component, Clone, Debug, Default)]
 pub struct RotationalInertiaTensorList2D{
-    pub inertia: HashMap<u32, f32>
+    pub inertia: HashMap<u32, Scalar>
 }
\ No newline at end of file
@@ -3,7 +3,10 @@
 pub mod plugin;
 pub mod simulation;
 pub mod components;
+pub mod collision;
+pub mod scalar;
 pub mod prelude{
     pub use crate::plugin::*;
     pub use crate::components::*;
+    pub use crate::scalar::*;
 }
\ No newline at end of file
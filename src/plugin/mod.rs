@@ -1,14 +1,90 @@
-use bevy::prelude::*;
-use super::simulation::UpdateRungeKutta;
+use bevy::{prelude::*, transform::TransformSystem, diagnostic::{Diagnostic, RegisterDiagnostic}};
+use super::simulation::{
+    UpdateRungeKutta, DiffEqSolverConfig, AdaptiveStepConfig, SolverStepReport, SymplecticSolverConfig, SolverMode,
+    SolverConfigAsset, SolverConfigLoader, ActiveSolverConfig, apply_solver_config_asset,
+    FlatlandTimestep, SimulationClock, FlatlandSet, sync_fixed_timestep, has_substeps_remaining, advance_simulation_clock, 
+    interpolate_transforms, UniformGravity2D, SimulationDiagnostics, update_simulation_diagnostics, SolverWorkspace,
+    update_mass_properties
+};
+use super::collision::{Contacts2D, ContactSolverConfig, BroadPhase2D, update_broad_phase, refit_broad_phase, detect_contacts, solve_constraints,
+    TimeOfImpact2D, solve_continuous_collisions, CollisionStarted, CollisionEnded, report_collisions, SleepConfig, SimulationIslands,
+    wake_bodies, wake_touched_islands, update_sleeping, TouchingPairs2D, JointBodies2D};
 
 
 pub struct FlatlandPhysicsPlugin{}
 impl Plugin for FlatlandPhysicsPlugin{
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, (
-            UpdateRungeKutta,
-            UpdateRungeKutta,
-            UpdateRungeKutta
-        ));
+        app
+            .init_resource::<DiffEqSolverConfig>()
+            .init_resource::<AdaptiveStepConfig>()
+            .init_resource::<SolverStepReport>()
+            .init_resource::<SymplecticSolverConfig>()
+            .init_resource::<SolverMode>()
+            .init_resource::<UniformGravity2D>()
+            .init_resource::<FlatlandTimestep>()
+            .init_resource::<SimulationClock>()
+            .init_resource::<SimulationDiagnostics>()
+            .init_resource::<SolverWorkspace>()
+            .init_resource::<Contacts2D>()
+            .init_resource::<ContactSolverConfig>()
+            .init_resource::<BroadPhase2D>()
+            .init_resource::<SleepConfig>()
+            .init_resource::<SimulationIslands>()
+            .init_resource::<TouchingPairs2D>()
+            .init_resource::<JointBodies2D>()
+            .init_resource::<FixedTime>()
+            .add_event::<TimeOfImpact2D>()
+            .add_event::<CollisionStarted>()
+            .add_event::<CollisionEnded>()
+            .configure_set(FixedUpdate, FlatlandSet::Step.run_if(has_substeps_remaining))
+            .configure_sets(FixedUpdate, (
+                FlatlandSet::Prepare, 
+                FlatlandSet::Integrate,
+                FlatlandSet::Collide,
+                FlatlandSet::Resolve,
+                FlatlandSet::Diagnostics
+            ).chain().in_set(FlatlandSet::Step))
+            .add_systems(PreUpdate, sync_fixed_timestep)
+            .add_systems(FixedUpdate, (
+                //woken bodies lose Sleeping before the solver runs
+                (advance_simulation_clock, update_mass_properties, (wake_bodies, apply_deferred).chain()).in_set(FlatlandSet::Prepare),
+                UpdateRungeKutta.in_set(FlatlandSet::Integrate),
+                (update_broad_phase, solve_continuous_collisions, detect_contacts, report_collisions, wake_touched_islands, apply_deferred)
+                    .chain().in_set(FlatlandSet::Collide),
+                (solve_constraints, refit_broad_phase, update_sleeping).chain().in_set(FlatlandSet::Resolve),
+                update_simulation_diagnostics.in_set(FlatlandSet::Diagnostics)
+            ))
+            .add_systems(PostUpdate, interpolate_transforms.before(TransformSystem::TransformPropagate));
+        for (id, name, suffix) in [
+            (SimulationDiagnostics::KINETIC_ENERGY, "flatland_kinetic_energy", "J"),
+            (SimulationDiagnostics::POTENTIAL_ENERGY, "flatland_potential_energy", "J"),
+            (SimulationDiagnostics::TOTAL_ENERGY, "flatland_total_energy", "J"),
+            (SimulationDiagnostics::ENERGY_DRIFT, "flatland_energy_drift", "J"),
+            (SimulationDiagnostics::LINEAR_MOMENTUM, "flatland_linear_momentum", "kg m/s"),
+            (SimulationDiagnostics::ANGULAR_MOMENTUM, "flatland_angular_momentum", "kg m^2/s"),
+            (BroadPhase2D::PROXIES, "flatland_broad_phase_proxies", ""),
+            (BroadPhase2D::CANDIDATE_PAIRS, "flatland_candidate_pairs", ""),
+            (BroadPhase2D::OVERLAP_TESTS, "flatland_broad_phase_overlap_tests", "")
+        ]{
+            app.register_diagnostic(Diagnostic::new(id, name, 120).with_suffix(suffix));
+        }
+    }
+}
+
+//Loads the solver config from a .solver.ron asset at <path> and keeps DiffEqSolverConfig in sync with it.
+//Used together with FlatlandPhysicsPlugin. Requires the AssetPlugin, and AssetPlugin::watch_for_changes for the config to update while the game runs
+pub struct SolverConfigAssetPlugin{
+    pub path: String
+}
+impl Plugin for SolverConfigAssetPlugin{
+    fn build(&self, app: &mut App) {
+        let path = self.path.clone();
+        app
+            .add_asset::<SolverConfigAsset>()
+            .init_asset_loader::<SolverConfigLoader>()
+            .add_systems(Startup, move |mut commands: Commands, server: Res<AssetServer>| {
+                commands.insert_resource(ActiveSolverConfig(server.load(path.as_str())));
+            })
+            .add_systems(PreUpdate, apply_solver_config_asset);
     }
 }
\ No newline at end of file
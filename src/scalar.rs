@@ -0,0 +1,42 @@
+use bevy::math::{Vec2, DVec2};
+
+//Floating point type used by the simulation and its components. 
+//Enable the f64 feature for levels large enough that single precision loses sub-pixel accuracy far from the origin
+#[cfg(not(feature = "f64"))]
+pub type Scalar = f32;
+#[cfg(not(feature = "f64"))]
+pub type Vector = Vec2;
+#[cfg(feature = "f64")]
+pub type Scalar = f64;
+#[cfg(feature = "f64")]
+pub type Vector = DVec2;
+#[cfg(not(feature = "f64"))]
+pub use std::f32::consts::PI;
+#[cfg(feature = "f64")]
+pub use std::f64::consts::PI;
+
+//Conversions between simulation vectors and the single precision vectors Bevy's transforms use
+pub trait VectorConversion{
+    fn to_vector(self) -> Vector;
+}
+impl VectorConversion for Vec2{
+    fn to_vector(self) -> Vector{
+        Vector::new(self.x as Scalar, self.y as Scalar)
+    }
+}
+impl VectorConversion for DVec2{
+    fn to_vector(self) -> Vector{
+        Vector::new(self.x as Scalar, self.y as Scalar)
+    }
+}
+pub fn to_vec2(vector: Vector) -> Vec2{
+    Vec2::new(to_f32(vector.x), to_f32(vector.y))
+}
+#[allow(clippy::unnecessary_cast)]
+pub fn to_f32(value: Scalar) -> f32{
+    value as f32
+}
+#[allow(clippy::unnecessary_cast)]
+pub fn to_f64(value: Scalar) -> f64{
+    value as f64
+}
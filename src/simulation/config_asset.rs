@@ -0,0 +1,180 @@
+use bevy::{asset::{AssetLoader, LoadContext, LoadedAsset}, reflect::{TypePath, TypeUuid}, utils::BoxedFuture};
+use serde::Deserialize;
+use super::*;
+
+//Solver configuration loaded from a .solver.ron file, applied to the DiffEqSolverConfig resource whenever it loads or changes on disk
+#[derive(TypeUuid, TypePath, Clone, Debug)]
+#[uuid = "60549297-b84f-40ac-a20e-40ee5b95ed24"]
+pub struct SolverConfigAsset{
+    pub config: DiffEqSolverConfig,
+    //Replaces the AdaptiveStepConfig resource when present
+    pub adaptive: Option<AdaptiveStepConfig>
+}
+
+//Handle of the solver config asset that currently drives the simulation
+#[derive(Resource)]
+pub struct ActiveSolverConfig(pub Handle<SolverConfigAsset>);
+
+//On disk layout of a SolverConfigAsset, e.g.
+//  (method: Preset("rk4"))
+//  (method: Tableau(nodes: [0.0, 1.0], weights: [0.5, 0.5], coeff_matrix: [[0.0, 0.0], [1.0, 0.0]], accuracy_order: 2))
+//  (method: Tableau(nodes: [0.0, 1.0], weights: [0.5, 0.5], coeff_matrix: [[0.0, 0.0], [1.0, 0.0]], accuracy_order: 2, 
+//      embedded_weights: Some([1.0, 0.0]), error_order: Some(1)))
+#[derive(Deserialize)]
+struct SolverConfigFile{
+    method: SolverMethod,
+    #[serde(default)]
+    stage_solver: Option<StageSolver>,
+    #[serde(default)]
+    adaptive: Option<AdaptiveStepConfig>
+}
+
+#[derive(Deserialize)]
+enum SolverMethod{
+    //Name of one of the DiffEqSolverConfig constructors, see DiffEqSolverConfig::preset
+    Preset(String),
+    SecondOrder(Scalar),
+    ThirdOrder(Scalar),
+    Tableau{
+        nodes: Vec<Scalar>,
+        weights: Vec<Scalar>,
+        coeff_matrix: Vec<Vec<Scalar>>,
+        //order of accuracy the tableau is checked against
+        accuracy_order: usize,
+        //weights of the second solution of an embedded pair, which need the order of the less accurate of the two solutions
+        #[serde(default)]
+        embedded_weights: Option<Vec<Scalar>>,
+        #[serde(default)]
+        error_order: Option<usize>
+    }
+}
+
+//Error for a preset name that doesn't match any constructor
+#[derive(Debug)]
+pub struct UnknownPresetError(pub String);
+impl std::fmt::Display for UnknownPresetError{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "unknown solver preset \"{}\"", self.0)
+    }
+}
+impl std::error::Error for UnknownPresetError{}
+
+//Error for a tableau that gives only one of embedded_weights and error_order
+#[derive(Debug)]
+pub struct IncompleteEmbeddedPairError{pub missing: &'static str}
+impl std::fmt::Display for IncompleteEmbeddedPairError{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "embedded pair is missing its {}", self.missing)
+    }
+}
+impl std::error::Error for IncompleteEmbeddedPairError{}
+
+impl SolverConfigAsset{
+    //Parses and validates the contents of a .solver.ron file
+    pub fn from_ron(bytes: &[u8]) -> Result<Self, bevy::asset::Error>{
+        let file: SolverConfigFile = ron::de::from_bytes(bytes)?;
+        let config = match file.method {
+            SolverMethod::Preset(name) => DiffEqSolverConfig::preset(&name).ok_or(UnknownPresetError(name))?,
+            SolverMethod::SecondOrder(a) => DiffEqSolverConfig::try_second_order(a)?,
+            SolverMethod::ThirdOrder(a) => DiffEqSolverConfig::try_third_order(a)?,
+            SolverMethod::Tableau{nodes, weights, coeff_matrix, accuracy_order, embedded_weights, error_order} => match (embedded_weights, error_order) {
+                (Some(embedded_weights), Some(error_order)) => 
+                    DiffEqSolverConfig::from_embedded_tableau(nodes, weights, coeff_matrix, accuracy_order, embedded_weights, error_order)?,
+                (Some(_), None) => return Err(IncompleteEmbeddedPairError{missing: "error_order"}.into()),
+                (None, Some(_)) => return Err(IncompleteEmbeddedPairError{missing: "embedded_weights"}.into()),
+                (None, None) => DiffEqSolverConfig::from_tableau(nodes, weights, coeff_matrix, accuracy_order)?
+            }
+        };
+        let config = match file.stage_solver {
+            Some(solver) => config.with_stage_solver(solver),
+            None => config
+        };
+        Ok(Self{config, adaptive: file.adaptive})
+    }
+}
+
+impl DiffEqSolverConfig{
+    //Function names of every parameterless constructor, accepted by preset
+    pub const PRESETS: [&'static str; 22] = [
+        "euler", "midpoint", "heun", "ralston",
+        "kutta_third", "heun_third", "wray_third", "ralston_third", "ssprk3",
+        "rk4", "rule3_8", "ralston_fourth",
+        "bogacki_shampine", "fehlberg", "dormand_prince", "cash_karp",
+        "backward_euler", "implicit_midpoint", "gauss_legendre4", "gauss_legendre6", "radau_iia3", "radau_iia5"
+    ];
+
+    //Looks up a parameterless constructor by its function name
+    pub fn preset(name: &str) -> Option<Self>{
+        Some(match name {
+            "euler" => Self::euler(),
+            "midpoint" => Self::midpoint(),
+            "heun" => Self::heun(),
+            "ralston" => Self::ralston(),
+            "kutta_third" => Self::kutta_third(),
+            "heun_third" => Self::heun_third(),
+            "wray_third" => Self::wray_third(),
+            "ralston_third" => Self::ralston_third(),
+            "ssprk3" => Self::ssprk3(),
+            "rk4" => Self::rk4(),
+            "rule3_8" => Self::rule3_8(),
+            "ralston_fourth" => Self::ralston_fourth(),
+            "bogacki_shampine" => Self::bogacki_shampine(),
+            "fehlberg" => Self::fehlberg(),
+            "dormand_prince" => Self::dormand_prince(),
+            "cash_karp" => Self::cash_karp(),
+            "backward_euler" => Self::backward_euler(),
+            "implicit_midpoint" => Self::implicit_midpoint(),
+            "gauss_legendre4" => Self::gauss_legendre4(),
+            "gauss_legendre6" => Self::gauss_legendre6(),
+            "radau_iia3" => Self::radau_iia3(),
+            "radau_iia5" => Self::radau_iia5(),
+            _ => return None
+        })
+    }
+}
+
+#[derive(Default)]
+pub struct SolverConfigLoader;
+impl AssetLoader for SolverConfigLoader{
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            load_context.set_default_asset(LoadedAsset::new(SolverConfigAsset::from_ron(bytes)?));
+            Ok(())
+        })
+    }
+    fn extensions(&self) -> &[&str] {
+        &["solver.ron"]
+    }
+}
+
+//Copies the active solver config asset into the solver resources when it finishes loading or is modified on disk.
+//Hot reloading needs AssetPlugin::watch_for_changes to be enabled
+pub fn apply_solver_config_asset(
+    mut events: EventReader<AssetEvent<SolverConfigAsset>>,
+    active: Option<Res<ActiveSolverConfig>>,
+    assets: Res<Assets<SolverConfigAsset>>,
+    mut config: ResMut<DiffEqSolverConfig>,
+    mut adaptive: ResMut<AdaptiveStepConfig>
+){
+    let Some(active) = active else {
+        return;
+    };
+    for event in events.iter(){
+        let (AssetEvent::Created{handle} | AssetEvent::Modified{handle}) = event else {
+            continue;
+        };
+        if *handle != active.0 {
+            continue;
+        }
+        if let Some(asset) = assets.get(handle) {
+            *config = asset.config.clone();
+            if let Some(asset_adaptive) = &asset.adaptive {
+                *adaptive = asset_adaptive.clone();
+            }
+        }
+    }
+}
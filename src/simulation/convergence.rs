@@ -0,0 +1,251 @@
+use super::*;
+use bevy::math::DVec2;
+
+//Test problems with closed form solutions, used to check that each tableau converges at the order it claims
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReferenceProblem{
+    //Unit mass on a spring anchored at the origin, x'' = -x
+    HarmonicOscillator,
+    //Body thrown under uniform gravity and linear drag, x'' = g - 0.5x'
+    Projectile,
+    //Underdamped spring, x'' = -4x - 0.4x'
+    DampedSpring,
+    //Pendulum of unit length released from rest at 1 radian, θ'' = -sin θ, solved through the angle of the body
+    Pendulum
+}
+impl ReferenceProblem{
+    pub const ALL: [Self; 4] = [Self::HarmonicOscillator, Self::Projectile, Self::DampedSpring, Self::Pendulum];
+    const GRAVITY: DVec2 = DVec2::new(0.0, -9.81);
+    const PROJECTILE_VELOCITY: DVec2 = DVec2::new(3.0, 4.0);
+    //without drag the trajectory is a parabola, which every method of order 2 or higher integrates exactly
+    const PROJECTILE_DRAG: f64 = 2.0;
+    const PENDULUM_AMPLITUDE: f64 = 1.0;
+
+    pub fn name(&self) -> &'static str{
+        match self {
+            Self::HarmonicOscillator => "harmonic oscillator",
+            Self::Projectile => "projectile",
+            Self::DampedSpring => "damped spring",
+            Self::Pendulum => "pendulum"
+        }
+    }
+    //Whether the forces only depend on position. Symplectic splittings drop to first order on the others, 
+    //since they evaluate drag and damping at a velocity from the wrong point of the step
+    pub fn is_conservative(&self) -> bool{
+        matches!(self, Self::HarmonicOscillator | Self::Pendulum)
+    }
+    pub fn end_time(&self) -> Scalar{
+        match self {
+            Self::Projectile => 3.0,
+            _ => 5.0
+        }
+    }
+    pub fn initial_state(&self) -> RigidBodyState2D{
+        match self {
+            Self::HarmonicOscillator | Self::DampedSpring => RigidBodyState2D{position: Vector::X, ..Default::default()},
+            Self::Projectile => RigidBodyState2D{velocity: Self::PROJECTILE_VELOCITY.to_vector(), ..Default::default()},
+            Self::Pendulum => RigidBodyState2D{angle: Self::PENDULUM_AMPLITUDE as Scalar, ..Default::default()}
+        }
+    }
+    //Closed form solution at time <t>, evaluated in double precision
+    pub fn exact(&self, t: f64) -> RigidBodyState2D{
+        match self {
+            Self::HarmonicOscillator => RigidBodyState2D{
+                position: DVec2::new(t.cos(), 0.0).to_vector(),
+                velocity: DVec2::new(-t.sin(), 0.0).to_vector(),
+                ..Default::default()
+            },
+            Self::Projectile => {
+                //the velocity relaxes towards the terminal velocity g/k
+                let (drag, velocity) = (Self::PROJECTILE_DRAG, Self::PROJECTILE_VELOCITY);
+                let terminal = Self::GRAVITY/drag;
+                let decay = (-drag*t).exp();
+                RigidBodyState2D{
+                    position: (terminal*t + (velocity - terminal)*((1.0 - decay)/drag)).to_vector(),
+                    velocity: (terminal + (velocity - terminal)*decay).to_vector(),
+                    ..Default::default()
+                }
+            },
+            Self::DampedSpring => {
+                //omega = 2, zeta = 0.1
+                let decay = 0.2;
+                let frequency = (4.0f64 - decay*decay).sqrt();
+                let envelope = (-decay*t).exp();
+                let (sin, cos) = (frequency*t).sin_cos();
+                RigidBodyState2D{
+                    position: DVec2::new(envelope*(cos + decay/frequency*sin), 0.0).to_vector(),
+                    velocity: DVec2::new(-envelope*(decay*decay/frequency + frequency)*sin, 0.0).to_vector(),
+                    ..Default::default()
+                }
+            },
+            Self::Pendulum => {
+                //θ(t) = 2 asin(k sn(K - t, k)) with k = sin(θ0/2)
+                let k = (Self::PENDULUM_AMPLITUDE/2.0).sin();
+                let quarter_period = complete_elliptic_k(k);
+                let (sn, cn, dn) = jacobi_elliptic(quarter_period - t, k);
+                let angle = 2.0*(k*sn).asin();
+                //dθ/dt = -2k cn dn / sqrt(1 - k^2 sn^2)
+                let angular_velocity = -2.0*k*cn*dn/(1.0 - k*k*sn*sn).sqrt();
+                RigidBodyState2D{angle: angle as Scalar, angular_velocity: angular_velocity as Scalar, ..Default::default()}
+            }
+        }
+    }
+    //Force model driving the problem through the regular force generators, None for the pendulum which acts on the angle directly
+    fn model(&self) -> Option<ForceModel2D>{
+        let spring = |damping| SpringForce2D{
+            a: SpringAnchor2D::Body{index: 0, local_anchor: Vector::ZERO},
+            b: SpringAnchor2D::World(Vector::ZERO),
+            rest_length: 0.0,
+            stiffness: 0.0,
+            damping
+        };
+        let free_body = BodyForces2D{gravity_scale: 1.0, ..Default::default()};
+        match self {
+            Self::HarmonicOscillator => Some(ForceModel2D{
+                bodies: vec![free_body],
+                springs: vec![SpringForce2D{stiffness: 1.0, ..spring(0.0)}],
+                ..Default::default()
+            }),
+            Self::Projectile => Some(ForceModel2D{
+                gravity: Self::GRAVITY.to_vector(),
+                bodies: vec![BodyForces2D{linear_drag: LinearDrag2D{linear: Self::PROJECTILE_DRAG as Scalar, angular: 0.0}, ..free_body}],
+                ..Default::default()
+            }),
+            Self::DampedSpring => Some(ForceModel2D{
+                bodies: vec![free_body],
+                springs: vec![SpringForce2D{stiffness: 4.0, ..spring(0.4)}],
+                ..Default::default()
+            }),
+            Self::Pendulum => None
+        }
+    }
+    fn derivative(&self, model: &Option<ForceModel2D>, states: &[RigidBodyState2D], derivatives: &mut [RigidBodyState2D]){
+        let body = RigidBody2D::new(1.0, 1.0);
+        match model {
+            Some(model) => model.derivatives(states, &[&body], derivatives),
+            None => derivatives[0] = states[0].derivative(Vector::ZERO, -states[0].angle.sin(), &body)
+        }
+    }
+}
+
+//Global error of one method on one problem over a sequence of halving step sizes
+#[derive(Clone, Debug)]
+pub struct ConvergenceResult{
+    pub method: String,
+    pub problem: ReferenceProblem,
+    pub step_sizes: Vec<Scalar>,
+    //Largest component of the difference from the exact solution at the end time
+    pub errors: Vec<Scalar>,
+    //Slope of log(error) against log(step size) between the smallest two errors above CONVERGENCE_ERROR_FLOOR, 
+    //None when fewer than two errors sit above it. This happens when a method is exact for the problem or reaches rounding error at the largest steps
+    pub observed_order: Option<Scalar>
+}
+
+//Errors below this are treated as rounding noise when estimating the order
+#[cfg(not(feature = "f64"))]
+pub const CONVERGENCE_ERROR_FLOOR: Scalar = 1e-5;
+#[cfg(feature = "f64")]
+pub const CONVERGENCE_ERROR_FLOOR: Scalar = 1e-10;
+
+//Step counts over ReferenceProblem::end_time for a method of <order>, halving the step from where the error follows its leading term
+//down to around CONVERGENCE_ERROR_FLOOR, so every method gets an estimate from at least two errors on every problem
+pub fn convergence_step_counts(order: usize) -> Vec<usize>{
+    #[cfg(not(feature = "f64"))]
+    let first = match order {
+        0 | 1 => 256,
+        2 => 32,
+        3 => 16,
+        4 => 4,
+        _ => 2
+    };
+    #[cfg(feature = "f64")]
+    let first = match order {
+        0 | 1 => 256,
+        2 => 128,
+        3 => 64,
+        4 => 32,
+        5 => 16,
+        _ => 8
+    };
+    (0..4).map(|halvings| first << halvings).collect()
+}
+
+//Integrates <problem> with <config> using fixed steps, once for every entry of <step_counts>
+pub fn measure_convergence(method: &str, config: &DiffEqSolverConfig, problem: ReferenceProblem, step_counts: &[usize]) -> ConvergenceResult{
+    let model = problem.model();
+    measure(method, problem, step_counts, |t, dt, states, workspace| {
+        config.step(t, dt, states, workspace, |_, stage_states, derivatives| problem.derivative(&model, stage_states, derivatives));
+    })
+}
+pub fn measure_symplectic_convergence(method: &str, config: &SymplecticSolverConfig, problem: ReferenceProblem, step_counts: &[usize]) -> ConvergenceResult{
+    let model = problem.model();
+    measure(method, problem, step_counts, |t, dt, states, workspace| {
+        config.step(t, dt, states, workspace, |_, stage_states, derivatives| problem.derivative(&model, stage_states, derivatives));
+    })
+}
+
+//Runs every DiffEqSolverConfig preset on every reference problem, over the step counts of the order its tableau satisfies
+pub fn convergence_table() -> Vec<ConvergenceResult>{
+    DiffEqSolverConfig::PRESETS.iter().flat_map(|name| {
+        let config = DiffEqSolverConfig::preset(name).expect("every listed preset has a constructor");
+        let step_counts = convergence_step_counts(config.accuracy_order());
+        ReferenceProblem::ALL.iter().map(move |problem| measure_convergence(name, &config, *problem, &step_counts)).collect::<Vec<_>>()
+    }).collect()
+}
+
+fn measure<S>(method: &str, problem: ReferenceProblem, step_counts: &[usize], mut step: S) -> ConvergenceResult
+    where S: FnMut(Scalar, Scalar, &mut [RigidBodyState2D], &mut SolverWorkspace)
+{
+    let end_time = problem.end_time();
+    let exact = problem.exact(to_f64(end_time)).components();
+    let mut workspace = SolverWorkspace::default();
+    let (step_sizes, errors): (Vec<Scalar>, Vec<Scalar>) = step_counts.iter().map(|steps| {
+        let dt = end_time/(*steps as Scalar);
+        let mut states = [problem.initial_state()];
+        for i in 0..*steps{
+            step(i as Scalar*dt, dt, &mut states, &mut workspace);
+        }
+        let result = states[0].components();
+        let error = (0..RigidBodyState2D::COMPONENTS).map(|i| (result[i] - exact[i]).abs()).fold(0.0, Scalar::max);
+        (dt, error)
+    }).unzip();
+    //slope between the two smallest errors still dominated by truncation, where the leading error term has taken over the most
+    let points: Vec<(Scalar, Scalar)> = step_sizes.iter().zip(errors.iter())
+        .filter(|(_, error)| **error > CONVERGENCE_ERROR_FLOOR && error.is_finite())
+        .map(|(dt, error)| (dt.ln(), error.ln()))
+        .collect();
+    let observed_order = match points.as_slice() {
+        [.., coarse, fine] => Some((coarse.1 - fine.1)/(coarse.0 - fine.0)),
+        _ => None
+    };
+    ConvergenceResult{method: method.to_string(), problem, step_sizes, errors, observed_order}
+}
+
+//Complete elliptic integral of the first kind K(k) through the arithmetic-geometric mean
+fn complete_elliptic_k(k: f64) -> f64{
+    let (mut a, mut b) = (1.0f64, (1.0 - k*k).sqrt());
+    while (a - b).abs() > 1e-15 {
+        (a, b) = ((a + b)/2.0, (a*b).sqrt());
+    }
+    std::f64::consts::PI/(2.0*a)
+}
+
+//Jacobi elliptic functions sn, cn and dn with modulus k, by descending Landen transformation (Abramowitz & Stegun 16.4)
+fn jacobi_elliptic(u: f64, k: f64) -> (f64, f64, f64){
+    let mut a = vec![1.0f64];
+    let mut c = vec![k];
+    let mut b = (1.0 - k*k).sqrt();
+    while c.last().unwrap().abs() > 1e-15 && a.len() < 32 {
+        let (an, bn) = (*a.last().unwrap(), b);
+        a.push((an + bn)/2.0);
+        c.push((an - bn)/2.0);
+        b = (an*bn).sqrt();
+    }
+    let n = a.len() - 1;
+    let mut phi = 2f64.powi(n as i32)*a[n]*u;
+    for i in (1..=n).rev(){
+        phi = (phi + (c[i]/a[i]*phi.sin()).asin())/2.0;
+    }
+    let (sn, cn) = phi.sin_cos();
+    (sn, cn, (1.0 - k*k*sn*sn).sqrt())
+}
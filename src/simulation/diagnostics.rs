@@ -0,0 +1,122 @@
+use bevy::diagnostic::{DiagnosticId, Diagnostics};
+use super::*;
+
+//Conserved quantities of the simulated bodies at one instant
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SimulationTotals{
+    pub kinetic_energy: Scalar,
+    //Potential energy of gravity, attractors and springs. Drag and external forces do work that shows up as drift
+    pub potential_energy: Scalar,
+    pub linear_momentum: Vector,
+    //Angular momentum around the world origin
+    pub angular_momentum: Scalar
+}
+impl SimulationTotals{
+    pub fn measure(model: &ForceModel2D, states: &[RigidBodyState2D], bodies: &[&RigidBody2D]) -> Self{
+        let mut totals = Self{
+            potential_energy: model.potential_energy(states, bodies),
+            ..Default::default()
+        };
+        for (state, body) in states.iter().zip(bodies.iter()){
+            let momentum = state.velocity*body.mass;
+            totals.kinetic_energy += 0.5*body.mass*state.velocity.length_squared() 
+                + 0.5*body.rotational_inertia*state.angular_velocity*state.angular_velocity;
+            totals.linear_momentum += momentum;
+            totals.angular_momentum += state.position.perp_dot(momentum) + body.rotational_inertia*state.angular_velocity;
+        }
+        totals
+    }
+    pub fn total_energy(&self) -> Scalar{
+        self.kinetic_energy + self.potential_energy
+    }
+}
+
+//Energy and momentum of every dynamic RigidBody2D after the latest simulation step, compared against a baseline.
+//The values are also published to the DiagnosticsStore under the ids below
+#[derive(Resource, Clone, Debug, Default)]
+pub struct SimulationDiagnostics{
+    pub current: SimulationTotals,
+    //Captured by the first measurement after startup or after reset_baseline
+    pub baseline: Option<SimulationTotals>
+}
+impl SimulationDiagnostics{
+    pub const KINETIC_ENERGY: DiagnosticId = DiagnosticId::from_u128(162498378645911958146095039134421690636);
+    pub const POTENTIAL_ENERGY: DiagnosticId = DiagnosticId::from_u128(40210014530443379912886321484434203182);
+    pub const TOTAL_ENERGY: DiagnosticId = DiagnosticId::from_u128(11196212209724110181898072169813531031);
+    pub const ENERGY_DRIFT: DiagnosticId = DiagnosticId::from_u128(276577089610809831785007998885932383956);
+    pub const LINEAR_MOMENTUM: DiagnosticId = DiagnosticId::from_u128(89726883327500929491130403374685146034);
+    pub const ANGULAR_MOMENTUM: DiagnosticId = DiagnosticId::from_u128(170437955795542010580929967737916413009);
+
+    pub fn reset_baseline(&mut self){
+        self.baseline = None;
+    }
+    pub fn energy_drift(&self) -> Scalar{
+        self.baseline.map(|baseline| self.current.total_energy() - baseline.total_energy()).unwrap_or(0.0)
+    }
+    //Energy drift as a fraction of the baseline energy, 0 when the baseline energy is 0
+    pub fn relative_energy_drift(&self) -> Scalar{
+        match self.baseline {
+            Some(baseline) if baseline.total_energy() != 0.0 => self.energy_drift()/baseline.total_energy().abs(),
+            _ => 0.0
+        }
+    }
+    pub fn linear_momentum_drift(&self) -> Vector{
+        self.baseline.map(|baseline| self.current.linear_momentum - baseline.linear_momentum).unwrap_or(Vector::ZERO)
+    }
+    pub fn angular_momentum_drift(&self) -> Scalar{
+        self.baseline.map(|baseline| self.current.angular_momentum - baseline.angular_momentum).unwrap_or(0.0)
+    }
+}
+
+impl ForceModel2D{
+    //Potential energy stored in the conservative force generators
+    pub fn potential_energy(&self, states: &[RigidBodyState2D], bodies: &[&RigidBody2D]) -> Scalar{
+        let mut energy = 0.0;
+        for (index, (state, body)) in states.iter().zip(bodies.iter()).enumerate(){
+            let gravity_scale = self.bodies.get(index).map(|forces| forces.gravity_scale).unwrap_or(1.0);
+            energy -= body.mass*gravity_scale*self.gravity.dot(state.position);
+            for (position, attractor) in self.attractors.iter(){
+                let distance = position.distance(state.position);
+                let scale = attractor.strength*body.mass;
+                //a zero min_distance would put an infinite well at the attractor itself
+                let min_distance = attractor.min_distance.max(Scalar::EPSILON);
+                energy += if distance >= min_distance {
+                    -scale/distance
+                } else {
+                    //the force is constant inside min_distance, so the potential is linear there
+                    scale*(distance - min_distance)/min_distance.powi(2) - scale/min_distance
+                };
+            }
+        }
+        for spring in self.springs.iter(){
+            let length = spring.a.world_point(states).0.distance(spring.b.world_point(states).0);
+            energy += 0.5*spring.stiffness*(length - spring.rest_length).powi(2);
+        }
+        energy
+    }
+}
+
+pub fn update_simulation_diagnostics(
+    generators: ForceGenerators,
+    bodies: Query<(Entity, &SimulationData, &RigidBody2D)>,
+    mut simulation_diagnostics: ResMut<SimulationDiagnostics>,
+    mut diagnostics: Diagnostics
+){
+    //bodies that aren't dynamic are driven from outside, so only dynamic ones are measured
+    let dynamic: Vec<(Entity, &SimulationData, &RigidBody2D)> = bodies.iter().filter(|(_, _, body)| body.is_dynamic()).collect();
+    let entities: Vec<Entity> = dynamic.iter().map(|(entity, _, _)| *entity).collect();
+    let states: Vec<RigidBodyState2D> = dynamic.iter().map(|(_, data, _)| data.state).collect();
+    let rigid_bodies: Vec<&RigidBody2D> = dynamic.iter().map(|(_, _, body)| *body).collect();
+    let totals = SimulationTotals::measure(&generators.model(&entities), &states, &rigid_bodies);
+    simulation_diagnostics.current = totals;
+    if simulation_diagnostics.baseline.is_none() {
+        simulation_diagnostics.baseline = Some(totals);
+    }
+    let drift = simulation_diagnostics.energy_drift();
+    diagnostics.add_measurement(SimulationDiagnostics::KINETIC_ENERGY, || to_f64(totals.kinetic_energy));
+    diagnostics.add_measurement(SimulationDiagnostics::POTENTIAL_ENERGY, || to_f64(totals.potential_energy));
+    diagnostics.add_measurement(SimulationDiagnostics::TOTAL_ENERGY, || to_f64(totals.total_energy()));
+    diagnostics.add_measurement(SimulationDiagnostics::ENERGY_DRIFT, || to_f64(drift));
+    diagnostics.add_measurement(SimulationDiagnostics::LINEAR_MOMENTUM, || to_f64(totals.linear_momentum.length()));
+    diagnostics.add_measurement(SimulationDiagnostics::ANGULAR_MOMENTUM, || to_f64(totals.angular_momentum));
+}
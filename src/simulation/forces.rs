@@ -0,0 +1,214 @@
+use bevy::{ecs::system::SystemParam, utils::HashMap};
+use super::*;
+
+/*
+    FORCE GENERATORS
+*/
+
+//Acceleration applied to every simulated body, scaled per body by GravityScale2D
+#[derive(Resource, Clone, Debug)]
+pub struct UniformGravity2D{
+    pub acceleration: Vector
+}
+impl Default for UniformGravity2D{
+    fn default() -> Self {
+        Self{acceleration: Vector::NEG_Y*9.81}
+    }
+}
+
+#[derive(Component, Clone, Copy, Debug)]
+pub struct GravityScale2D(pub Scalar);
+
+//Drag proportional to velocity, F = -k*v
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct LinearDrag2D{
+    pub linear: Scalar,
+    pub angular: Scalar
+}
+
+//Drag proportional to the square of velocity, F = -k*|v|*v
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct QuadraticDrag2D{
+    pub linear: Scalar,
+    pub angular: Scalar
+}
+
+//Pulls every body towards the GlobalTransform of this entity with an inverse square law, like a point mass.
+//Inside <min_distance> the pull stops growing so bodies passing through the center don't get flung away
+#[derive(Component, Clone, Copy, Debug)]
+pub struct PointAttractor2D{
+    pub strength: Scalar,
+    pub min_distance: Scalar
+}
+
+//Spring with a damper between an anchor on <body_a> and an anchor on <body_b>. 
+//Without <body_b>, <anchor_b> is a point in world space
+#[derive(Component, Clone, Copy, Debug)]
+pub struct DampedSpring2D{
+    pub body_a: Entity,
+    pub local_anchor_a: Vector,
+    pub body_b: Option<Entity>,
+    pub anchor_b: Vector,
+    pub rest_length: Scalar,
+    pub stiffness: Scalar,
+    pub damping: Scalar
+}
+
+/*
+    FORCE MODEL
+*/
+
+//Forces acting on one body that only depend on its own state
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BodyForces2D{
+    pub gravity_scale: Scalar,
+    pub linear_drag: LinearDrag2D,
+    pub quadratic_drag: QuadraticDrag2D,
+    //force and torque accumulated on SimulationData, constant over the step
+    pub external_force: Vector,
+    pub external_torque: Scalar
+}
+
+//End of a spring, either on the body at <index> in the solver's state slice or fixed in the world
+#[derive(Clone, Copy, Debug)]
+pub enum SpringAnchor2D{
+    //<local_anchor> is relative to the body's centre of mass
+    Body{index: usize, local_anchor: Vector},
+    World(Vector)
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct SpringForce2D{
+    pub a: SpringAnchor2D,
+    pub b: SpringAnchor2D,
+    pub rest_length: Scalar,
+    pub stiffness: Scalar,
+    pub damping: Scalar
+}
+
+//Snapshot of every force generator for one simulation step, indexed like the solver's state slice.
+//Forces are re-evaluated from it for every stage of the solver
+#[derive(Clone, Debug, Default)]
+pub struct ForceModel2D{
+    pub gravity: Vector,
+    pub bodies: Vec<BodyForces2D>,
+    //world position and attractor settings
+    pub attractors: Vec<(Vector, PointAttractor2D)>,
+    pub springs: Vec<SpringForce2D>
+}
+impl ForceModel2D{
+    //Writes the time derivative of every state into <derivatives> from the sum of all forces at <states>
+    pub fn derivatives(&self, states: &[RigidBodyState2D], bodies: &[&RigidBody2D], derivatives: &mut [RigidBodyState2D]){
+        let mut forces = vec![(Vector::ZERO, 0.0); states.len()];
+        for (index, (state, body)) in states.iter().zip(bodies.iter()).enumerate(){
+            let generators = self.bodies.get(index).copied().unwrap_or_default();
+            let (force, torque) = &mut forces[index];
+            *force += generators.external_force;
+            *torque += generators.external_torque;
+            *force += self.gravity*generators.gravity_scale*body.mass;
+            *force -= state.velocity*generators.linear_drag.linear;
+            *torque -= state.angular_velocity*generators.linear_drag.angular;
+            *force -= state.velocity*state.velocity.length()*generators.quadratic_drag.linear;
+            *torque -= state.angular_velocity*state.angular_velocity.abs()*generators.quadratic_drag.angular;
+            for (position, attractor) in self.attractors.iter(){
+                let offset = *position - state.position;
+                let distance = offset.length().max(attractor.min_distance);
+                if distance > 0.0 {
+                    *force += offset.normalize_or_zero()*attractor.strength*body.mass/(distance*distance);
+                }
+            }
+        }
+        for spring in self.springs.iter(){
+            let (point_a, velocity_a) = spring.a.world_point(states);
+            let (point_b, velocity_b) = spring.b.world_point(states);
+            let offset = point_b - point_a;
+            let length = offset.length();
+            let direction = if length > 0.0 { offset/length } else { Vector::ZERO };
+            //positive magnitude pulls the two ends together
+            let magnitude = spring.stiffness*(length - spring.rest_length) + spring.damping*(velocity_b - velocity_a).dot(direction);
+            spring.a.apply(states, &mut forces, direction*magnitude);
+            spring.b.apply(states, &mut forces, -direction*magnitude);
+        }
+        for (((state, body), (force, torque)), derivative) in states.iter().zip(bodies.iter()).zip(forces).zip(derivatives.iter_mut()){
+            *derivative = state.derivative(force, torque, body);
+        }
+    }
+    //Pairs of bodies whose derivatives depend on each other's state, which are the two ends of every spring between bodies
+    pub fn couplings(&self) -> impl Iterator<Item = (usize, usize)> + '_{
+        self.springs.iter().filter_map(|spring| match (spring.a, spring.b) {
+            (SpringAnchor2D::Body{index: a, ..}, SpringAnchor2D::Body{index: b, ..}) if a != b => Some((a, b)),
+            _ => None
+        })
+    }
+}
+impl SpringAnchor2D{
+    //Position and velocity of the anchor in world space
+    pub(super) fn world_point(&self, states: &[RigidBodyState2D]) -> (Vector, Vector){
+        match *self {
+            Self::Body{index, local_anchor} => {
+                let state = &states[index];
+                let arm = Vector::from_angle(state.angle).rotate(local_anchor);
+                (state.position + arm, state.velocity + arm.perp()*state.angular_velocity)
+            },
+            Self::World(point) => (point, Vector::ZERO)
+        }
+    }
+    fn apply(&self, states: &[RigidBodyState2D], forces: &mut [(Vector, Scalar)], force: Vector){
+        if let Self::Body{index, local_anchor} = *self {
+            let arm = Vector::from_angle(states[index].angle).rotate(local_anchor);
+            forces[index].0 += force;
+            forces[index].1 += arm.perp_dot(force);
+        }
+    }
+}
+
+//Every force generator in the world, used by the solver to build a ForceModel2D at the start of each step
+#[derive(SystemParam)]
+pub struct ForceGenerators<'w, 's>{
+    gravity: Option<Res<'w, UniformGravity2D>>,
+    bodies: Query<'w, 's, (Option<&'static GravityScale2D>, Option<&'static LinearDrag2D>, Option<&'static QuadraticDrag2D>)>,
+    attractors: Query<'w, 's, (&'static GlobalTransform, &'static PointAttractor2D)>,
+    springs: Query<'w, 's, &'static DampedSpring2D>,
+    anchors: Query<'w, 's, &'static GlobalTransform>,
+    centers: Query<'w, 's, &'static RigidBody2D>
+}
+impl<'w, 's> ForceGenerators<'w, 's>{
+    //<entities> holds the entity of every body in the order of the solver's state slice
+    pub fn model(&self, entities: &[Entity]) -> ForceModel2D{
+        let indices: HashMap<Entity, usize> = entities.iter().enumerate().map(|(index, entity)| (*entity, index)).collect();
+        let anchor = |entity: Option<Entity>, local_anchor: Vector| -> Option<SpringAnchor2D> {
+            let Some(entity) = entity else {
+                return Some(SpringAnchor2D::World(local_anchor));
+            };
+            if let Some(index) = indices.get(&entity) {
+                let center = self.centers.get(entity).map(|body| body.center_of_mass).unwrap_or(Vector::ZERO);
+                return Some(SpringAnchor2D::Body{index: *index, local_anchor: local_anchor - center});
+            }
+            //entities that aren't stepped, like sleeping bodies or ones without SimulationData, act as fixed anchors
+            let transform = self.anchors.get(entity).ok()?;
+            Some(SpringAnchor2D::World(transform.transform_point(to_vec2(local_anchor).extend(0.0)).truncate().to_vector()))
+        };
+        ForceModel2D{
+            gravity: self.gravity.as_ref().map(|gravity| gravity.acceleration).unwrap_or(Vector::ZERO),
+            bodies: entities.iter().map(|entity| {
+                let (scale, linear_drag, quadratic_drag) = self.bodies.get(*entity).unwrap_or((None, None, None));
+                BodyForces2D{
+                    gravity_scale: scale.map(|scale| scale.0).unwrap_or(1.0),
+                    linear_drag: linear_drag.copied().unwrap_or_default(),
+                    quadratic_drag: quadratic_drag.copied().unwrap_or_default(),
+                    ..Default::default()
+                }
+            }).collect(),
+            attractors: self.attractors.iter()
+                .map(|(transform, attractor)| (transform.translation().truncate().to_vector(), *attractor))
+                .collect(),
+            springs: self.springs.iter().filter_map(|spring| Some(SpringForce2D{
+                a: anchor(Some(spring.body_a), spring.local_anchor_a)?,
+                b: anchor(spring.body_b, spring.anchor_b)?,
+                rest_length: spring.rest_length,
+                stiffness: spring.stiffness,
+                damping: spring.damping
+            })).collect()
+        }
+    }
+}
@@ -0,0 +1,269 @@
+use nalgebra::{DMatrix, DVector};
+use super::*;
+
+//Method used to solve the coupled stage equations of implicit Runge-Kutta tableaus, k_i = f(y_n + dt * sum(a_ij * k_j))
+#[derive(Clone, Copy, Debug, PartialEq, serde::Deserialize)]
+pub enum StageSolver{
+    //Re-evaluates every stage from the previous guess. 
+    //Cheap per iteration, but only converges while dt is small compared to the stiffness of the system, which defeats the purpose for stiff springs
+    FixedPoint{max_iterations: usize, tolerance: Scalar},
+    //Simplified Newton iteration using a finite difference Jacobian of each body's derivative, evaluated once per step.
+    //Coupling between bodies is left out of the Jacobian, so bodies connected to each other converge more slowly than bodies connected to the world
+    Newton{max_iterations: usize, tolerance: Scalar}
+}
+impl StageSolver{
+    //Default stage tolerance, kept well below the truncation error of the 4th order tableaus at the fixed timestep
+    #[cfg(not(feature = "f64"))]
+    pub const DEFAULT_TOLERANCE: Scalar = 1e-6;
+    #[cfg(feature = "f64")]
+    pub const DEFAULT_TOLERANCE: Scalar = 1e-11;
+}
+impl Default for StageSolver{
+    fn default() -> Self {
+        Self::Newton{max_iterations: 10, tolerance: Self::DEFAULT_TOLERANCE}
+    }
+}
+
+impl DiffEqSolverConfig{
+//Implicit Constructors:
+
+    pub fn backward_euler() -> Self{
+        Self{
+            order: 1,
+            nodes: vec![1.0],
+            weights: vec![1.0],
+            coeff_matrix: vec![vec![1.0]],
+            stage_solver: Some(StageSolver::default()),
+            embedded: None
+        }
+    }
+    pub fn implicit_midpoint() -> Self{
+        Self{
+            order: 1,
+            nodes: vec![0.5],
+            weights: vec![1.0],
+            coeff_matrix: vec![vec![0.5]],
+            stage_solver: Some(StageSolver::default()),
+            embedded: None
+        }
+    }
+    //Two stage Gauss-Legendre method, fourth order accurate
+    pub fn gauss_legendre4() -> Self{
+        let r = (3.0 as Scalar).sqrt()/6.0;
+        Self{
+            order: 2,
+            nodes: vec![0.5 - r, 0.5 + r],
+            weights: vec![0.5, 0.5],
+            coeff_matrix: vec![
+                vec![0.25, 0.25 - r],
+                vec![0.25 + r, 0.25]
+            ],
+            stage_solver: Some(StageSolver::default()),
+            embedded: None
+        }
+    }
+    //Three stage Gauss-Legendre method, sixth order accurate
+    pub fn gauss_legendre6() -> Self{
+        let r = (15.0 as Scalar).sqrt();
+        Self{
+            order: 3,
+            nodes: vec![0.5 - r/10.0, 0.5, 0.5 + r/10.0],
+            weights: vec![5.0/18.0, 4.0/9.0, 5.0/18.0],
+            coeff_matrix: vec![
+                vec![5.0/36.0, 2.0/9.0 - r/15.0, 5.0/36.0 - r/30.0],
+                vec![5.0/36.0 + r/24.0, 2.0/9.0, 5.0/36.0 - r/24.0],
+                vec![5.0/36.0 + r/30.0, 2.0/9.0 + r/15.0, 5.0/36.0]
+            ],
+            stage_solver: Some(StageSolver::default()),
+            embedded: None
+        }
+    }
+    //Two stage Radau IIA method, third order accurate and L-stable
+    pub fn radau_iia3() -> Self{
+        Self{
+            order: 2,
+            nodes: vec![1.0/3.0, 1.0],
+            weights: vec![3.0/4.0, 1.0/4.0],
+            coeff_matrix: vec![
+                vec![5.0/12.0, -1.0/12.0],
+                vec![3.0/4.0, 1.0/4.0]
+            ],
+            stage_solver: Some(StageSolver::default()),
+            embedded: None
+        }
+    }
+    //Three stage Radau IIA method, fifth order accurate and L-stable
+    pub fn radau_iia5() -> Self{
+        let r = (6.0 as Scalar).sqrt();
+        Self{
+            order: 3,
+            nodes: vec![(4.0 - r)/10.0, (4.0 + r)/10.0, 1.0],
+            weights: vec![(16.0 - r)/36.0, (16.0 + r)/36.0, 1.0/9.0],
+            coeff_matrix: vec![
+                vec![(88.0 - 7.0*r)/360.0, (296.0 - 169.0*r)/1800.0, (-2.0 + 3.0*r)/225.0],
+                vec![(296.0 + 169.0*r)/1800.0, (88.0 + 7.0*r)/360.0, (-2.0 - 3.0*r)/225.0],
+                vec![(16.0 - r)/36.0, (16.0 + r)/36.0, 1.0/9.0]
+            ],
+            stage_solver: Some(StageSolver::default()),
+            embedded: None
+        }
+    }
+
+    //Replaces the method used to solve the stage equations, has no effect on explicit tableaus
+    pub fn with_stage_solver(mut self, solver: StageSolver) -> Self{
+        if self.stage_solver.is_some() {
+            self.stage_solver = Some(solver);
+        }
+        self
+    }
+
+    //Solves the stage equations of an implicit tableau, leaving the stage derivatives in the workspace.
+    //Returns false if the iteration ran out of iterations before reaching its tolerance
+    pub(super) fn solve_implicit_stages<F>(
+        &self, 
+        solver: StageSolver,
+        t: Scalar, 
+        dt: Scalar, 
+        states: &[RigidBodyState2D], 
+        workspace: &mut SolverWorkspace, 
+        derivative: &mut F
+    ) -> bool where F: FnMut(Scalar, &[RigidBodyState2D], &mut [RigidBodyState2D]) {
+        let (max_iterations, tolerance) = match solver {
+            StageSolver::FixedPoint{max_iterations, tolerance} => (max_iterations, tolerance),
+            StageSolver::Newton{max_iterations, tolerance} => (max_iterations, tolerance)
+        };
+        workspace.resize(self.order, states.len());
+        workspace.stage_values.resize_with(self.order, Vec::new);
+        for values in workspace.stage_values.iter_mut(){
+            values.resize(states.len(), RigidBodyState2D::default());
+        }
+        //every stage starts from the derivative at the beginning of the step
+        derivative(t, states, &mut workspace.stages[0]);
+        for stage in 1..self.order{
+            let (first, rest) = workspace.stages.split_at_mut(1);
+            rest[stage - 1].copy_from_slice(&first[0]);
+        }
+        let jacobians = match solver {
+            StageSolver::Newton{..} => self.newton_matrices(t, dt, states, workspace, derivative),
+            StageSolver::FixedPoint{..} => vec![]
+        };
+        for _ in 0..max_iterations{
+            //f(y_n + dt * sum(a_ij * k_j)) for every stage using the current guess of k
+            for stage in 0..self.order{
+                for (body, stage_state) in workspace.stage_states.iter_mut().enumerate(){
+                    *stage_state = states[body];
+                    for (j, a) in self.coeff_matrix[stage].iter().enumerate(){
+                        if *a != 0.0 {
+                            *stage_state += workspace.stages[j][body]*(dt*a);
+                        }
+                    }
+                }
+                derivative(t + self.nodes[stage]*dt, &workspace.stage_states, &mut workspace.stage_values[stage]);
+            }
+            let mut residual: Scalar = 0.0;
+            for stage in 0..self.order{
+                for body in 0..states.len(){
+                    let guess = workspace.stages[stage][body].components();
+                    let value = workspace.stage_values[stage][body].components();
+                    for i in 0..RigidBodyState2D::COMPONENTS{
+                        residual = residual.max((guess[i] - value[i]).abs()/(1.0 + value[i].abs()));
+                    }
+                }
+            }
+            if residual <= tolerance {
+                return true;
+            }
+            match solver {
+                StageSolver::FixedPoint{..} => {
+                    for stage in 0..self.order{
+                        let (stages, values) = (&mut workspace.stages[stage], &workspace.stage_values[stage]);
+                        stages.copy_from_slice(values);
+                    }
+                },
+                StageSolver::Newton{..} => {
+                    for (body, lu) in jacobians.iter().enumerate(){
+                        let Some(lu) = lu else {
+                            continue;
+                        };
+                        //right hand side is -(k - f(Y)) for every stage of this body
+                        let rhs = DVector::from_iterator(self.order*RigidBodyState2D::COMPONENTS, (0..self.order).flat_map(|stage| {
+                            let guess = workspace.stages[stage][body].components();
+                            let value = workspace.stage_values[stage][body].components();
+                            (0..RigidBodyState2D::COMPONENTS).map(move |i| value[i] - guess[i])
+                        }));
+                        let Some(delta) = lu.solve(&rhs) else {
+                            continue;
+                        };
+                        for stage in 0..self.order{
+                            let offset = stage*RigidBodyState2D::COMPONENTS;
+                            let mut components = [0.0; RigidBodyState2D::COMPONENTS];
+                            components.copy_from_slice(&delta.as_slice()[offset..offset + RigidBodyState2D::COMPONENTS]);
+                            workspace.stages[stage][body] += RigidBodyState2D::from_components(components);
+                        }
+                    }
+                }
+            }
+        }
+        false
+    }
+
+    //LU decomposition of the Newton matrix (I - dt * A ⊗ J) for every body, where J is the Jacobian of that body's derivative
+    //with respect to its own state at the start of the step. Bodies with a singular matrix get None and fall back to keeping their guess.
+    //Bodies that aren't coupled are perturbed together, so the finite differences take a derivative evaluation per component
+    //and per group rather than per body
+    fn newton_matrices<F>(
+        &self, 
+        t: Scalar, 
+        dt: Scalar, 
+        states: &[RigidBodyState2D], 
+        workspace: &mut SolverWorkspace, 
+        derivative: &mut F
+    ) -> Vec<Option<nalgebra::LU<Scalar, nalgebra::Dyn, nalgebra::Dyn>>> where F: FnMut(Scalar, &[RigidBodyState2D], &mut [RigidBodyState2D]) {
+        const N: usize = RigidBodyState2D::COMPONENTS;
+        //greedy colouring of the coupling graph, no two bodies of a group depend on each other
+        let mut neighbours = vec![Vec::new(); states.len()];
+        for (a, b) in workspace.couplings.iter().filter(|(a, b)| *a < states.len() && *b < states.len()){
+            neighbours[*a].push(*b);
+            neighbours[*b].push(*a);
+        }
+        let mut groups: Vec<usize> = Vec::with_capacity(states.len());
+        for (body, adjacent) in neighbours.iter().enumerate(){
+            let taken: Vec<usize> = adjacent.iter().filter(|other| **other < body).map(|other| groups[*other]).collect();
+            groups.push((0..).find(|group| !taken.contains(group)).unwrap_or(0));
+        }
+        let group_count = groups.iter().max().map_or(0, |max| max + 1);
+
+        let base = workspace.stages[0].clone();
+        let mut jacobians = vec![[[0.0; N]; N]; states.len()];
+        let mut perturbed_states = states.to_vec();
+        let mut perturbed_values = vec![RigidBodyState2D::default(); states.len()];
+        let epsilon = |body: usize, column: usize| Scalar::EPSILON.sqrt()*(1.0 + states[body].components()[column].abs());
+        for group in 0..group_count{
+            for column in 0..N{
+                for body in (0..states.len()).filter(|body| groups[*body] == group){
+                    let mut components = states[body].components();
+                    components[column] += epsilon(body, column);
+                    perturbed_states[body] = RigidBodyState2D::from_components(components);
+                }
+                derivative(t, &perturbed_states, &mut perturbed_values);
+                for body in (0..states.len()).filter(|body| groups[*body] == group){
+                    let value = perturbed_values[body].components();
+                    let reference = base[body].components();
+                    for row in 0..N{
+                        jacobians[body][row][column] = (value[row] - reference[row])/epsilon(body, column);
+                    }
+                    perturbed_states[body] = states[body];
+                }
+            }
+        }
+        jacobians.iter().map(|jacobian| {
+            let size = self.order*N;
+            let matrix = DMatrix::from_fn(size, size, |row, column| {
+                let identity = if row == column { 1.0 } else { 0.0 };
+                identity - dt*self.coeff_matrix[row/N][column/N]*jacobian[row%N][column%N]
+            });
+            let lu = matrix.lu();
+            if lu.is_invertible() { Some(lu) } else { None }
+        }).collect()
+    }
+}
@@ -0,0 +1,75 @@
+use bevy::{ecs::system::SystemParam, utils::HashSet};
+use crate::components::*;
+use super::*;
+
+//Collider components of every shape removed since the system last ran
+#[derive(SystemParam)]
+pub struct RemovedColliders<'w, 's>{
+    boxes: RemovedComponents<'w, 's, BoxCollider2D>,
+    circles: RemovedComponents<'w, 's, CircleCollider2D>,
+    capsules: RemovedComponents<'w, 's, CapsuleCollider2D>,
+    polygons: RemovedComponents<'w, 's, ConvexPolygonCollider2D>,
+    polylines: RemovedComponents<'w, 's, PolylineCollider2D>
+}
+impl<'w, 's> RemovedColliders<'w, 's>{
+    pub fn any(&mut self) -> bool{
+        //counting drains every reader, so the same removals aren't seen again next time
+        self.boxes.iter().count() + self.circles.iter().count() + self.capsules.iter().count() 
+            + self.polygons.iter().count() + self.polylines.iter().count() > 0
+    }
+}
+
+//Recomputes the mass, centre of mass and inertia of every body with a RotationalInertiaTensorList2D.
+//A body is made of the collider on its own entity and those on its direct children, placed by the child's Transform.
+//Children with their own RigidBody2D are separate bodies and don't count. Bodies left without colliders keep their last values
+#[allow(clippy::type_complexity)]
+pub fn update_mass_properties(
+    changed_colliders: Query<(Entity, Option<&Parent>), (WithCollider, Or<(ChangedCollider, Changed<ColliderDensity2D>)>)>,
+    moved_colliders: Query<&Parent, (WithCollider, Without<RigidBody2D>, Changed<Transform>)>,
+    mut removed_colliders: RemovedColliders,
+    mut bodies: Query<(
+        Entity, &mut RigidBody2D, &mut RotationalInertiaTensorList2D, Option<&mut SimulationData>, 
+        Option<Ref<Children>>, Option<ColliderComponents>, Option<&ColliderDensity2D>
+    )>,
+    parts: Query<(ColliderComponents, Option<&ColliderDensity2D>, &Transform), Without<RigidBody2D>>
+){
+    //the body a removed collider belonged to can't be looked up anymore, so removals recompute every body
+    let any_removed = removed_colliders.any();
+    let mut dirty: HashSet<Entity> = bodies.iter_mut()
+        .filter(|(_, _, list, _, children, ..)| any_removed || list.is_added() || children.as_ref().is_some_and(|children| children.is_changed()))
+        .map(|(entity, ..)| entity)
+        .collect();
+    for (entity, parent) in changed_colliders.iter(){
+        dirty.insert(entity);
+        dirty.extend(parent.map(|parent| parent.get()));
+    }
+    dirty.extend(moved_colliders.iter().map(|parent| parent.get()));
+    for entity in dirty{
+        let Ok((_, mut body, mut list, data, children, collider, own_density)) = bodies.get_mut(entity) else {continue};
+        let density_of = |density: Option<&ColliderDensity2D>| density.map(|density| density.0).unwrap_or(DEFAULT_COLLIDER_DENSITY);
+        let mut colliders: Vec<(Entity, MassProperties2D)> = Vec::new();
+        if let Some(collider) = collider {
+            colliders.push((entity, Collider2D::from_components(collider).mass_properties(density_of(own_density))));
+        }
+        for child in children.iter().flat_map(|children| children.iter()){
+            let Ok((collider, child_density, transform)) = parts.get(*child) else {continue};
+            let translation = transform.translation.truncate().to_vector();
+            let angle = transform.rotation.to_euler(EulerRot::ZYX).0 as Scalar;
+            colliders.push((*child, Collider2D::from_components(collider).mass_properties(density_of(child_density)).transformed(translation, angle)));
+        }
+        if colliders.is_empty() {
+            continue;
+        }
+        let total: MassProperties2D = colliders.iter().map(|(_, properties)| *properties).sum();
+        list.inertia = colliders.iter().map(|(entity, properties)| (entity.index(), properties.inertia_about(total.center))).collect();
+        //keep the simulated state on the centre of mass without moving the body
+        if let Some(mut data) = data {
+            let offset = total.center - body.center_of_mass;
+            data.state = data.state.shifted(offset);
+            data.previous = data.previous.shifted(offset);
+        }
+        body.mass = total.mass;
+        body.rotational_inertia = total.inertia;
+        body.center_of_mass = total.center;
+    }
+}
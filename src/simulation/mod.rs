@@ -1,18 +1,52 @@
+use std::ops::{Add, AddAssign, Mul, Sub};
 use bevy::prelude::*;
-use super::components::RigidBody2D;
+use super::components::{RigidBody2D, RigidBodyType2D, Sleeping};
+use super::scalar::*;
+mod symplectic;
+mod implicit;
+mod validation;
+mod config_asset;
+mod timestep;
+mod forces;
+mod diagnostics;
+mod snapshot;
+mod mass;
+pub mod convergence;
+pub use symplectic::*;
+pub use implicit::*;
+pub use validation::*;
+pub use config_asset::*;
+pub use timestep::*;
+pub use forces::*;
+pub use diagnostics::*;
+pub use snapshot::*;
+pub use mass::*;
 
 //Contains all of the config values for the differential equation solver which uses the Runge Kutta Method
-#[derive(Resource)]
+#[derive(Resource, Clone, Debug)]
 pub struct DiffEqSolverConfig{
     //Number of intermediate steps in the Runge Kutta Method
     order: usize,
     //Vector of length <order> holding node constants for Runge-Kutta Method, commonly notated as (c)
-    nodes: Vec<f32>, 
+    nodes: Vec<Scalar>, 
     //Vector of length <order> holding weight constants for Runge-Kutta Method, commonly notated as (b)
-    weights: Vec<f32>, 
+    weights: Vec<Scalar>, 
     //<order>x<order> Matrix holding the coefficients for the Runge-Kutta Method, commonly notated as (a). 
-    //matrix is lower triangular for explicit forms of the method
-    coeff_matrix: Vec<Vec<f32>> 
+    //matrix is strictly lower triangular for explicit forms of the method, any other entry makes the method implicit
+    coeff_matrix: Vec<Vec<Scalar>>,
+    //Solver for the stage equations of implicit methods, None for explicit methods
+    stage_solver: Option<StageSolver>,
+    //Second weight vector for embedded methods, used to estimate the local error of each step
+    embedded: Option<EmbeddedWeights>
+}
+
+//Weights of the second solution of an embedded Runge-Kutta pair, commonly notated as (b*)
+#[derive(Clone, Debug)]
+struct EmbeddedWeights{
+    //Vector of length <order>, the difference between the two solutions estimates the error of a step
+    weights: Vec<Scalar>,
+    //Order of accuracy of the lower order solution in the pair, which sets how step sizes are rescaled
+    error_order: usize
 }
 impl DiffEqSolverConfig{
 //Constructors:
@@ -20,12 +54,14 @@ impl DiffEqSolverConfig{
     pub fn euler() -> Self {
         Self{
             order: 1,
-            nodes: vec![0f32],
-            weights: vec![1f32],
-            coeff_matrix: vec![vec![0f32]]
+            nodes: vec![0.0],
+            weights: vec![1.0],
+            coeff_matrix: vec![vec![0.0]],
+            stage_solver: None,
+            embedded: None
         }
     }
-    pub fn second_order(a: f32) -> Self{
+    pub fn second_order(a: Scalar) -> Self{
         Self{
             order: 2,
             nodes: vec![0.0, a],
@@ -33,7 +69,9 @@ impl DiffEqSolverConfig{
             coeff_matrix: vec![
                 vec![0.0, 0.0],
                 vec![a, 0.0]
-            ]
+            ],
+            stage_solver: None,
+            embedded: None
         }
     }
     pub fn midpoint() -> Self {
@@ -41,7 +79,7 @@ impl DiffEqSolverConfig{
     }
     pub fn heun() -> Self{Self::second_order(1.0)}
     pub fn ralston() -> Self{Self::second_order(2.0/3.0)}
-    pub fn third_order(a: f32) -> Self{
+    pub fn third_order(a: Scalar) -> Self{
         assert_ne!(a, 1.0);
         assert_ne!(a, 0.0);
         assert_ne!(a, 2.0/3.0);
@@ -57,7 +95,9 @@ impl DiffEqSolverConfig{
                 vec![0.0, 0.0, 0.0],
                 vec![a, 0.0, 0.0],
                 vec![1.0 + (1.0-a)/(a*(3.0*a-2.0)), (a-1.0)/(a*(3.0*a-2.0)), 0.0]
-            ]
+            ],
+            stage_solver: None,
+            embedded: None
         }
     }
     pub fn kutta_third() -> Self{
@@ -69,7 +109,9 @@ impl DiffEqSolverConfig{
                 vec![0.0, 0.0, 0.0],
                 vec![0.5, 0.0, 0.0],
                 vec![-1.0, 2.0, 0.0]
-            ] 
+            ],
+            stage_solver: None,
+            embedded: None 
         }
     }
     pub fn heun_third() -> Self{
@@ -81,7 +123,9 @@ impl DiffEqSolverConfig{
                 vec![0.0, 0.0, 0.0],
                 vec![1.0/3.0, 0.0, 0.0],
                 vec![0.0, 2.0/3.0, 0.0]
-            ] 
+            ],
+            stage_solver: None,
+            embedded: None 
         }
     }
     pub fn wray_third() -> Self{
@@ -93,7 +137,9 @@ impl DiffEqSolverConfig{
                 vec![0.0, 0.0, 0.0],
                 vec![8.0/15.0, 0.0, 0.0],
                 vec![1.0/4.0, 5.0/12.0, 0.0]
-            ] 
+            ],
+            stage_solver: None,
+            embedded: None 
         }
     }
     pub fn ralston_third() -> Self{
@@ -105,7 +151,9 @@ impl DiffEqSolverConfig{
                 vec![0.0, 0.0, 0.0],
                 vec![1.0/2.0, 0.0, 0.0],
                 vec![0.0, 3.0/4.0, 0.0]
-            ] 
+            ],
+            stage_solver: None,
+            embedded: None 
         }
     }
     pub fn ssprk3() -> Self{
@@ -117,7 +165,9 @@ impl DiffEqSolverConfig{
                 vec![0.0, 0.0, 0.0],
                 vec![1.0, 0.0, 0.0],
                 vec![1.0/4.0, 1.0/4.0, 0.0]
-            ] 
+            ],
+            stage_solver: None,
+            embedded: None 
         }
     }
     pub fn rk4() -> Self {
@@ -130,7 +180,9 @@ impl DiffEqSolverConfig{
                 vec![0.5, 0.0, 0.0, 0.0],
                 vec![0.0, 0.5, 0.0, 0.0],
                 vec![0.0, 0.0, 1.0, 0.0]
-            ]
+            ],
+            stage_solver: None,
+            embedded: None
         }
     }
     pub fn rule3_8() -> Self {
@@ -143,29 +195,560 @@ impl DiffEqSolverConfig{
                 vec![1.0/3.0, 0.0, 0.0, 0.0],
                 vec![-1.0/3.0, 1.0, 0.0, 0.0],
                 vec![1.0, -1.0, 1.0, 0.0]
-            ]
+            ],
+            stage_solver: None,
+            embedded: None
         }
     }
+    //Ralston's fourth order method with minimum truncation error, from the closed forms so it stays fourth order in double precision
     pub fn ralston_fourth() -> Self {
+        let r = (5.0 as Scalar).sqrt();
+        Self{
+            order: 4,
+            nodes: vec![0.0, 2.0/5.0, (14.0 - 3.0*r)/16.0, 1.0],
+            weights: vec![
+                (263.0 + 24.0*r)/1812.0,
+                (125.0 - 1000.0*r)/3828.0,
+                (3426304.0 + 1661952.0*r)/5924787.0,
+                (30.0 - 4.0*r)/123.0
+            ],
+            coeff_matrix: vec![
+                vec![0.0, 0.0, 0.0, 0.0],
+                vec![2.0/5.0, 0.0, 0.0, 0.0],
+                vec![(-2889.0 + 1428.0*r)/1024.0, (3785.0 - 1620.0*r)/1024.0, 0.0, 0.0],
+                vec![(-3365.0 + 2094.0*r)/6040.0, (-975.0 - 3046.0*r)/2552.0, (467040.0 + 203968.0*r)/240845.0, 0.0]
+            ],
+            stage_solver: None,
+            embedded: None
+        }
+    }
+
+//Embedded Constructors:
+
+    pub fn bogacki_shampine() -> Self {
         Self{
             order: 4,
-            nodes: vec![0.0, 0.4, 0.45573725, 1.0],
-            weights: vec![0.17476028, -0.55148066, 1.20553560, 0.17118478],
+            nodes: vec![0.0, 0.5, 0.75, 1.0],
+            weights: vec![2.0/9.0, 1.0/3.0, 4.0/9.0, 0.0],
             coeff_matrix: vec![
                 vec![0.0, 0.0, 0.0, 0.0],
-                vec![0.4, 0.0, 0.0, 0.0],
-                vec![0.29697761, 0.15875964, 0.0, 0.0],
-                vec![0.21810040, -3.05096516, 3.83286476, 0.0]
-            ]
+                vec![0.5, 0.0, 0.0, 0.0],
+                vec![0.0, 0.75, 0.0, 0.0],
+                vec![2.0/9.0, 1.0/3.0, 4.0/9.0, 0.0]
+            ],
+            stage_solver: None,
+            embedded: Some(EmbeddedWeights{
+                weights: vec![7.0/24.0, 1.0/4.0, 1.0/3.0, 1.0/8.0],
+                error_order: 2
+            })
+        }
+    }
+    pub fn fehlberg() -> Self {
+        Self{
+            order: 6,
+            nodes: vec![0.0, 1.0/4.0, 3.0/8.0, 12.0/13.0, 1.0, 1.0/2.0],
+            weights: vec![25.0/216.0, 0.0, 1408.0/2565.0, 2197.0/4104.0, -1.0/5.0, 0.0],
+            coeff_matrix: vec![
+                vec![0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+                vec![1.0/4.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+                vec![3.0/32.0, 9.0/32.0, 0.0, 0.0, 0.0, 0.0],
+                vec![1932.0/2197.0, -7200.0/2197.0, 7296.0/2197.0, 0.0, 0.0, 0.0],
+                vec![439.0/216.0, -8.0, 3680.0/513.0, -845.0/4104.0, 0.0, 0.0],
+                vec![-8.0/27.0, 2.0, -3544.0/2565.0, 1859.0/4104.0, -11.0/40.0, 0.0]
+            ],
+            stage_solver: None,
+            embedded: Some(EmbeddedWeights{
+                weights: vec![16.0/135.0, 0.0, 6656.0/12825.0, 28561.0/56430.0, -9.0/50.0, 2.0/55.0],
+                error_order: 4
+            })
+        }
+    }
+    pub fn dormand_prince() -> Self {
+        Self{
+            order: 7,
+            nodes: vec![0.0, 1.0/5.0, 3.0/10.0, 4.0/5.0, 8.0/9.0, 1.0, 1.0],
+            weights: vec![35.0/384.0, 0.0, 500.0/1113.0, 125.0/192.0, -2187.0/6784.0, 11.0/84.0, 0.0],
+            coeff_matrix: vec![
+                vec![0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+                vec![1.0/5.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+                vec![3.0/40.0, 9.0/40.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+                vec![44.0/45.0, -56.0/15.0, 32.0/9.0, 0.0, 0.0, 0.0, 0.0],
+                vec![19372.0/6561.0, -25360.0/2187.0, 64448.0/6561.0, -212.0/729.0, 0.0, 0.0, 0.0],
+                vec![9017.0/3168.0, -355.0/33.0, 46732.0/5247.0, 49.0/176.0, -5103.0/18656.0, 0.0, 0.0],
+                vec![35.0/384.0, 0.0, 500.0/1113.0, 125.0/192.0, -2187.0/6784.0, 11.0/84.0, 0.0]
+            ],
+            stage_solver: None,
+            embedded: Some(EmbeddedWeights{
+                weights: vec![5179.0/57600.0, 0.0, 7571.0/16695.0, 393.0/640.0, -92097.0/339200.0, 187.0/2100.0, 1.0/40.0],
+                error_order: 4
+            })
+        }
+    }
+    pub fn cash_karp() -> Self {
+        Self{
+            order: 6,
+            nodes: vec![0.0, 1.0/5.0, 3.0/10.0, 3.0/5.0, 1.0, 7.0/8.0],
+            weights: vec![37.0/378.0, 0.0, 250.0/621.0, 125.0/594.0, 0.0, 512.0/1771.0],
+            coeff_matrix: vec![
+                vec![0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+                vec![1.0/5.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+                vec![3.0/40.0, 9.0/40.0, 0.0, 0.0, 0.0, 0.0],
+                vec![3.0/10.0, -9.0/10.0, 6.0/5.0, 0.0, 0.0, 0.0],
+                vec![-11.0/54.0, 5.0/2.0, -70.0/27.0, 35.0/27.0, 0.0, 0.0],
+                vec![1631.0/55296.0, 175.0/512.0, 575.0/13824.0, 44275.0/110592.0, 253.0/4096.0, 0.0]
+            ],
+            stage_solver: None,
+            embedded: Some(EmbeddedWeights{
+                weights: vec![2825.0/27648.0, 0.0, 18575.0/48384.0, 13525.0/55296.0, 277.0/14336.0, 1.0/4.0],
+                error_order: 4
+            })
+        }
+    }
+}
+
+impl Default for DiffEqSolverConfig{
+    fn default() -> Self {
+        Self::rk4()
+    }
+}
+impl DiffEqSolverConfig{
+//Solver:
+
+    pub fn is_embedded(&self) -> bool{
+        self.embedded.is_some()
+    }
+    pub fn is_implicit(&self) -> bool{
+        self.stage_solver.is_some()
+    }
+
+    //Advances every state in <states> by <dt> using the tableau held by this config.
+    //<derivative> receives the time and the intermediate states of every body for a stage, 
+    //and must write the time derivative of each state into the output slice.
+    //Returns false if the stage equations of an implicit method didn't converge, explicit methods always return true
+    pub fn step<F>(
+        &self, 
+        t: Scalar, 
+        dt: Scalar, 
+        states: &mut [RigidBodyState2D], 
+        workspace: &mut SolverWorkspace, 
+        mut derivative: F
+    ) -> bool where F: FnMut(Scalar, &[RigidBodyState2D], &mut [RigidBodyState2D]) {
+        let converged = self.compute_stages(t, dt, states, workspace, &mut derivative);
+        //y_n+1 = y_n + dt * sum(b_i * k_i)
+        for (body, state) in states.iter_mut().enumerate(){
+            *state += workspace.weighted_sum(&self.weights, body, dt);
+        }
+        converged
+    }
+
+    //Advances <states> by a full frame of length <dt>. 
+    //Embedded methods split the frame into as many substeps as <adaptive> requires to keep the estimated error in tolerance,
+    //all other methods take a single step
+    pub fn advance<F>(
+        &self, 
+        t: Scalar, 
+        dt: Scalar, 
+        states: &mut [RigidBodyState2D], 
+        workspace: &mut SolverWorkspace, 
+        adaptive: &AdaptiveStepConfig,
+        mut derivative: F
+    ) -> SolverStepReport where F: FnMut(Scalar, &[RigidBodyState2D], &mut [RigidBodyState2D]) {
+        let Some(embedded) = &self.embedded else {
+            let converged = self.step(t, dt, states, workspace, derivative);
+            return SolverStepReport{
+                accepted_steps: 1, 
+                unconverged_steps: usize::from(!converged),
+                last_step_size: dt,
+                ..Default::default()
+            };
+        };
+        let mut report = SolverStepReport::default();
+        let mut time = t;
+        let mut remaining = dt;
+        let mut step_size = workspace.next_step_size.unwrap_or(dt).clamp(adaptive.min_step.min(dt), dt);
+        while remaining > dt*Scalar::EPSILON {
+            //the last allowed substep has to cover the rest of the frame
+            let forced = report.accepted_steps + report.rejected_steps + 1 >= adaptive.max_substeps;
+            let h = if forced { remaining } else { step_size.min(remaining) };
+            let converged = self.compute_stages(time, h, states, workspace, &mut derivative);
+            let error = workspace.error_norm(states, &self.weights, &embedded.weights, h, adaptive);
+            if error <= 1.0 || forced || h <= adaptive.min_step {
+                for (body, state) in states.iter_mut().enumerate(){
+                    *state += workspace.weighted_sum(&self.weights, body, h);
+                }
+                time += h;
+                remaining -= h;
+                report.accepted_steps += 1;
+                report.unconverged_steps += usize::from(!converged);
+                report.max_error = report.max_error.max(error);
+                report.last_step_size = h;
+            }else{
+                report.rejected_steps += 1;
+            }
+            //optimal step size for the error order of the pair, limited so a single bad estimate can't swing it too far
+            //a non-finite estimate means the step blew up, so it is retried as small as allowed rather than grown
+            let factor = if !error.is_finite() {
+                adaptive.max_shrink
+            } else if error > 0.0 {
+                adaptive.safety_factor*error.powf(-1.0/(embedded.error_order as Scalar + 1.0))
+            } else {
+                adaptive.max_growth
+            };
+            step_size = (h*factor.clamp(adaptive.max_shrink, adaptive.max_growth)).max(adaptive.min_step);
+        }
+        workspace.next_step_size = Some(step_size);
+        report
+    }
+
+    //Fills the workspace with the derivative of every body at each stage of a step of length <dt>
+    fn compute_stages<F>(
+        &self, 
+        t: Scalar, 
+        dt: Scalar, 
+        states: &[RigidBodyState2D], 
+        workspace: &mut SolverWorkspace, 
+        derivative: &mut F
+    ) -> bool where F: FnMut(Scalar, &[RigidBodyState2D], &mut [RigidBodyState2D]) {
+        if let Some(solver) = self.stage_solver {
+            return self.solve_implicit_stages(solver, t, dt, states, workspace, derivative);
+        }
+        workspace.resize(self.order, states.len());
+        for stage in 0..self.order{
+            //y_n + dt * sum(a_ij * k_j)
+            for (body, stage_state) in workspace.stage_states.iter_mut().enumerate(){
+                *stage_state = states[body];
+                for (j, a) in self.coeff_matrix[stage][..stage].iter().enumerate(){
+                    if *a != 0.0 {
+                        *stage_state += workspace.stages[j][body]*(dt*a);
+                    }
+                }
+            }
+            derivative(t + self.nodes[stage]*dt, &workspace.stage_states, &mut workspace.stages[stage]);
+        }
+        true
+    }
+}
+
+//Scratch buffers reused between solver steps so that stepping doesn't allocate every frame
+#[derive(Resource, Default)]
+pub struct SolverWorkspace{
+    //<order> vectors holding the derivative of every body at each stage, commonly notated as (k)
+    stages: Vec<Vec<RigidBodyState2D>>,
+    //intermediate state of every body for the stage currently being evaluated
+    stage_states: Vec<RigidBodyState2D>,
+    //derivative at the current guess of every stage, only used by implicit methods
+    stage_values: Vec<Vec<RigidBodyState2D>>,
+    //step size the adaptive solver will try first on the next frame
+    next_step_size: Option<Scalar>,
+    //pairs of bodies whose derivatives depend on each other, which can't share a finite difference evaluation of the Newton Jacobians
+    couplings: Vec<(usize, usize)>
+}
+impl SolverWorkspace{
+    //Sets the bodies coupled by force generators for the next steps, indexed like the solver's state slice.
+    //Bodies left out are assumed to only depend on their own state, see ForceModel2D::couplings
+    pub fn set_couplings(&mut self, couplings: impl IntoIterator<Item = (usize, usize)>){
+        self.couplings.clear();
+        self.couplings.extend(couplings);
+    }
+    fn resize(&mut self, order: usize, bodies: usize){
+        self.stages.resize_with(order, Vec::new);
+        for stage in self.stages.iter_mut(){
+            stage.resize(bodies, RigidBodyState2D::default());
+        }
+        self.stage_states.resize(bodies, RigidBodyState2D::default());
+    }
+    //dt * sum(w_i * k_i) for a single body
+    fn weighted_sum(&self, weights: &[Scalar], body: usize, dt: Scalar) -> RigidBodyState2D{
+        weights.iter().zip(self.stages.iter())
+            .filter(|(w, _)| **w != 0.0)
+            .fold(RigidBodyState2D::default(), |acc, (w, stage)| acc + stage[body]*(dt*w))
+    }
+    //Root mean square of the difference between the two solutions of an embedded pair, 
+    //scaled so that a value of 1 sits exactly on the tolerance
+    fn error_norm(
+        &self, 
+        states: &[RigidBodyState2D], 
+        weights: &[Scalar], 
+        embedded_weights: &[Scalar], 
+        dt: Scalar, 
+        adaptive: &AdaptiveStepConfig
+    ) -> Scalar{
+        if states.is_empty() {
+            return 0.0;
         }
+        let difference: Vec<Scalar> = weights.iter().zip(embedded_weights.iter()).map(|(b, e)| b - e).collect();
+        let sum: Scalar = states.iter().enumerate().map(|(body, state)| {
+            let error = self.weighted_sum(&difference, body, dt).components();
+            let start = state.components();
+            let end = (*state + self.weighted_sum(weights, body, dt)).components();
+            (0..RigidBodyState2D::COMPONENTS).map(|i| {
+                let scale = adaptive.absolute_tolerance + adaptive.relative_tolerance*start[i].abs().max(end[i].abs());
+                (error[i]/scale).powi(2)
+            }).sum::<Scalar>()
+        }).sum();
+        (sum/(states.len()*RigidBodyState2D::COMPONENTS) as Scalar).sqrt()
     }
 }
 
+//Step size control for embedded Runge-Kutta methods, ignored by methods without error estimates
+#[derive(Resource, Clone, Debug, serde::Deserialize)]
+#[serde(default)]
+pub struct AdaptiveStepConfig{
+    pub absolute_tolerance: Scalar,
+    pub relative_tolerance: Scalar,
+    //Substeps at or below this size are accepted regardless of their error so the solver always makes progress
+    pub min_step: Scalar,
+    //Maximum number of substeps (accepted and rejected) per frame, the last one is forced to finish the frame
+    pub max_substeps: usize,
+    //Multiplier on the optimal step size estimate, keeps the next step safely below the tolerance
+    pub safety_factor: Scalar,
+    //Bounds on how much the step size can change between consecutive substeps
+    pub max_shrink: Scalar,
+    pub max_growth: Scalar
+}
+impl Default for AdaptiveStepConfig{
+    fn default() -> Self {
+        Self{
+            absolute_tolerance: 1e-3,
+            relative_tolerance: 1e-3,
+            min_step: 1e-5,
+            max_substeps: 64,
+            safety_factor: 0.9,
+            max_shrink: 0.2,
+            max_growth: 5.0
+        }
+    }
+}
+
+//Summary of the work the solver did over the last frame
+#[derive(Resource, Clone, Copy, Debug, Default)]
+pub struct SolverStepReport{
+    pub accepted_steps: usize,
+    pub rejected_steps: usize,
+    //Largest scaled error estimate of the accepted substeps, values above 1 mean a step was forced out of tolerance.
+    //Always 0 for methods without an embedded error estimate
+    pub max_error: Scalar,
+    pub last_step_size: Scalar,
+    //Accepted steps of an implicit method whose stage equations didn't converge within the iteration limit
+    pub unconverged_steps: usize
+}
+
+#[allow(non_snake_case, clippy::too_many_arguments)]
 pub fn UpdateRungeKutta(
-    mut moving_items: Query<(&mut Transform, &mut SimulationData, &GlobalTransform, &RigidBody2D)>
+    mode: Res<SolverMode>,
+    config: Res<DiffEqSolverConfig>,
+    symplectic: Res<SymplecticSolverConfig>,
+    timestep: Res<FlatlandTimestep>,
+    clock: Res<SimulationClock>,
+    adaptive: Res<AdaptiveStepConfig>,
+    mut report: ResMut<SolverStepReport>,
+    generators: ForceGenerators,
+    mut workspace: ResMut<SolverWorkspace>,
+    mut moving_items: Query<(Entity, &mut SimulationData, &RigidBody2D), Without<Sleeping>>
 ){
+    let dt = timestep.step;
+    //the clock has already been advanced to the end of this step
+    let t = clock.elapsed - dt;
+    //bodies are stepped in entity order rather than query order, which changes whenever a body moves between tables,
+    //so that replaying from a FlatlandSnapshot sums everything in the same order
+    let mut items: Vec<_> = moving_items.iter_mut().collect();
+    items.sort_unstable_by_key(|(entity, _, _)| *entity);
+    //bodies that aren't dynamic go through the solver too, where their infinite mass leaves them moving at the velocity they are driven with
+    for (_, data, body) in items.iter_mut(){
+        data.drive(body, dt);
+        data.consume_impulses(body);
+    }
+    let mut states: Vec<RigidBodyState2D> = items.iter().map(|(_, data, _)| data.state).collect();
+    let bodies: Vec<&RigidBody2D> = items.iter().map(|(_, _, body)| *body).collect();
+    let entities: Vec<Entity> = items.iter().map(|(entity, _, _)| *entity).collect();
+    let mut model = generators.model(&entities);
+    for ((_, data, _), forces) in items.iter().zip(model.bodies.iter_mut()){
+        forces.external_force = data.force;
+        forces.external_torque = data.torque;
+    }
+    workspace.set_couplings(model.couplings());
+    let derivative = |_, stage_states: &[RigidBodyState2D], derivatives: &mut [RigidBodyState2D]| {
+        model.derivatives(stage_states, &bodies, derivatives);
+    };
+    *report = match *mode {
+        SolverMode::RungeKutta => config.advance(t, dt, &mut states, &mut workspace, &adaptive, derivative),
+        SolverMode::Symplectic => {
+            symplectic.step(t, dt, &mut states, &mut workspace, derivative);
+            SolverStepReport{accepted_steps: 1, last_step_size: dt, ..Default::default()}
+        }
+    };
+    for ((_, data, _), state) in items.iter_mut().zip(states){
+        data.previous = data.state;
+        data.state = state;
+        data.clear_forces();
+    }
+}
+
+//Simulation state of a RigidBody2D, owned by the solver. Transform is overwritten from it every frame.
+//Intermediate stage states live in the solver's SolverWorkspace, since springs couple the stages of different bodies.
+//Forces and impulses are accumulated between steps, apply them from FixedUpdate systems ordered before FlatlandSet::Step
+//so each step sees them exactly once
+#[derive(Component, Clone, Debug, Default, PartialEq)]
+pub struct SimulationData{
+    pub state: RigidBodyState2D,
+    //state before the last simulation step, rendered transforms are interpolated between this and state
+    pub previous: RigidBodyState2D,
+    //external force and torque acting over the next simulation step, cleared after it
+    pub force: Vector,
+    pub torque: Scalar,
+    //instantaneous change in momentum applied at the start of the next simulation step
+    pub linear_impulse: Vector,
+    pub angular_impulse: Scalar,
+    //seconds the body's kinetic energy has stayed under SleepConfig::energy_threshold, reset when it falls asleep
+    pub idle_time: Scalar,
+    //position and angle a KinematicPositionBased body moves to over the next simulation step, cleared by it
+    pub kinematic_target: Option<(Vector, Scalar)>
+}
+impl SimulationData{
+    //Starts at the transform origin, which update_mass_properties moves onto the centre of mass of compound bodies
+    pub fn from_transform(transform: &Transform) -> Self{
+        let state = RigidBodyState2D{
+            position: transform.translation.truncate().to_vector(),
+            angle: transform.rotation.to_euler(EulerRot::ZYX).0 as Scalar,
+            ..Default::default()
+        };
+        Self{state, previous: state, ..Default::default()}
+    }
+    pub fn with_velocity(mut self, velocity: Vector, angular_velocity: Scalar) -> Self{
+        self.state.velocity = velocity;
+        self.state.angular_velocity = angular_velocity;
+        self
+    }
+
+    pub fn apply_force(&mut self, force: Vector){
+        self.force += force;
+    }
+    //Applies <force> at <point> in world space, which also produces a torque around the body's position
+    pub fn apply_force_at_point(&mut self, force: Vector, point: Vector){
+        self.force += force;
+        self.torque += (point - self.state.position).perp_dot(force);
+    }
+    pub fn apply_torque(&mut self, torque: Scalar){
+        self.torque += torque;
+    }
+    pub fn apply_impulse(&mut self, impulse: Vector){
+        self.linear_impulse += impulse;
+    }
+    pub fn apply_impulse_at_point(&mut self, impulse: Vector, point: Vector){
+        self.linear_impulse += impulse;
+        self.angular_impulse += (point - self.state.position).perp_dot(impulse);
+    }
+    pub fn apply_angular_impulse(&mut self, impulse: Scalar){
+        self.angular_impulse += impulse;
+    }
+    //Moves a KinematicPositionBased body over the next step so its centre of mass ends up at <position>, turned to <angle>
+    pub fn set_kinematic_target(&mut self, position: Vector, angle: Scalar){
+        self.kinematic_target = Some((position, angle));
+    }
+    //Velocity of the body at <point> in world space, which is how fast it moves anything touching it there
+    pub fn velocity_at_point(&self, point: Vector) -> Vector{
+        self.state.velocity + (point - self.state.position).perp()*self.state.angular_velocity
+    }
+
+    //Sets the velocity a body that isn't dynamic moves with over the next step of length <dt>, called by the solver at the start of a step
+    pub fn drive(&mut self, body: &RigidBody2D, dt: Scalar){
+        match body.body_type {
+            RigidBodyType2D::Dynamic | RigidBodyType2D::KinematicVelocityBased => {},
+            RigidBodyType2D::Static => {
+                self.state.velocity = Vector::ZERO;
+                self.state.angular_velocity = 0.0;
+            },
+            RigidBodyType2D::KinematicPositionBased => {
+                let (position, angle) = self.kinematic_target.take().unwrap_or((self.state.position, self.state.angle));
+                self.state.velocity = (position - self.state.position)/dt;
+                //the shorter way round, wrapped to (-PI, PI], so a target just past a half turn doesn't spin the body all the way back
+                let turn = PI - (PI - (angle - self.state.angle)).rem_euclid(2.0*PI);
+                self.state.angular_velocity = turn/dt;
+            }
+        }
+    }
 
+    //Turns the accumulated impulses into velocity, called by the solver at the start of a step
+    pub fn consume_impulses(&mut self, body: &RigidBody2D){
+        self.state.velocity += self.linear_impulse*body.inverse_mass();
+        self.state.angular_velocity += self.angular_impulse*body.inverse_rotational_inertia();
+        self.linear_impulse = Vector::ZERO;
+        self.angular_impulse = 0.0;
+    }
+    pub fn clear_forces(&mut self){
+        self.force = Vector::ZERO;
+        self.torque = 0.0;
+    }
 }
 
-#[derive(Component)]
-pub struct SimulationData{}
\ No newline at end of file
+//Position and velocity of a body in the plane. 
+//The time derivative of a state is stored in the same type, with velocity in the position slots and acceleration in the velocity slots
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct RigidBodyState2D{
+    pub position: Vector,
+    pub angle: Scalar,
+    pub velocity: Vector,
+    pub angular_velocity: Scalar
+}
+impl RigidBodyState2D{
+    pub const COMPONENTS: usize = 6;
+
+    pub fn components(&self) -> [Scalar; Self::COMPONENTS]{
+        [self.position.x, self.position.y, self.angle, self.velocity.x, self.velocity.y, self.angular_velocity]
+    }
+    pub fn from_components(components: [Scalar; Self::COMPONENTS]) -> Self{
+        Self{
+            position: Vector::new(components[0], components[1]),
+            angle: components[2],
+            velocity: Vector::new(components[3], components[4]),
+            angular_velocity: components[5]
+        }
+    }
+    //The same motion, followed at a point <offset> away in the body's local frame
+    pub fn shifted(&self, offset: Vector) -> Self{
+        let arm = Vector::from_angle(self.angle).rotate(offset);
+        Self{position: self.position + arm, velocity: self.velocity + arm.perp()*self.angular_velocity, ..*self}
+    }
+    //Time derivative of this state for a body under the given net force and torque
+    pub fn derivative(&self, force: Vector, torque: Scalar, body: &RigidBody2D) -> Self{
+        Self{
+            position: self.velocity,
+            angle: self.angular_velocity,
+            velocity: force*body.inverse_mass(),
+            angular_velocity: torque*body.inverse_rotational_inertia()
+        }
+    }
+}
+impl Add for RigidBodyState2D{
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        Self{
+            position: self.position + rhs.position,
+            angle: self.angle + rhs.angle,
+            velocity: self.velocity + rhs.velocity,
+            angular_velocity: self.angular_velocity + rhs.angular_velocity
+        }
+    }
+}
+impl AddAssign for RigidBodyState2D{
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+impl Sub for RigidBodyState2D{
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        self + rhs*-1.0
+    }
+}
+impl Mul<Scalar> for RigidBodyState2D{
+    type Output = Self;
+    fn mul(self, rhs: Scalar) -> Self {
+        Self{
+            position: self.position*rhs,
+            angle: self.angle*rhs,
+            velocity: self.velocity*rhs,
+            angular_velocity: self.angular_velocity*rhs
+        }
+    }
+}
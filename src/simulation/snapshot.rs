@@ -0,0 +1,133 @@
+use std::time::Duration;
+use bevy::ecs::{system::Command, query::Has};
+use crate::collision::{Contacts2D, Joint2D, TouchingPairs2D, JointBodies2D, SimulationIslands};
+use crate::components::CollidingEntities;
+use super::*;
+
+//Copy of everything the flatland solver carries from one step to the next.
+//Restoring it and replaying the same inputs with the same DiffEqSolverConfig reproduces the original steps bit for bit,
+//which is what instant retries and rollback replays rely on
+#[derive(Resource, Clone, Debug)]
+pub struct FlatlandSnapshot{
+    pub elapsed: Scalar,
+    pub steps: u64,
+    //real time FixedTime had accumulated towards the next step
+    pub accumulated: Duration,
+    //step size the adaptive solver was going to try next
+    next_step_size: Option<Scalar>,
+    //every body with SimulationData, in entity order
+    pub bodies: Vec<BodySnapshot2D>,
+    //contacts of the last step, which carry the impulses the contact solver warm starts from
+    pub contacts: Contacts2D,
+    //every joint in entity order, with the impulses the solver warm starts it from
+    pub joints: Vec<(Entity, Joint2D)>,
+    //collider pairs of the last step, which decide the collision events of the next one and through them which bodies wake up
+    pub touching: TouchingPairs2D,
+    //bodies of every joint, which wake when their joint is removed
+    pub joint_bodies: JointBodies2D,
+    pub islands: SimulationIslands,
+    //every CollidingEntities component in entity order
+    pub colliding: Vec<(Entity, CollidingEntities)>
+}
+#[derive(Clone, Debug)]
+pub struct BodySnapshot2D{
+    pub entity: Entity,
+    pub data: SimulationData,
+    pub body: RigidBody2D,
+    pub sleeping: bool
+}
+impl FlatlandSnapshot{
+    //Take snapshots between simulation steps, from Update or an exclusive system outside FlatlandSet::Step
+    pub fn capture(world: &mut World) -> Self{
+        let clock = world.get_resource::<SimulationClock>().cloned().unwrap_or_default();
+        let next_step_size = world.get_resource::<SolverWorkspace>().and_then(|workspace| workspace.next_step_size);
+        let mut bodies: Vec<BodySnapshot2D> = world.query::<(Entity, &SimulationData, &RigidBody2D, Has<Sleeping>)>().iter(world)
+            .map(|(entity, data, body, sleeping)| BodySnapshot2D{entity, data: data.clone(), body: body.clone(), sleeping})
+            .collect();
+        bodies.sort_unstable_by_key(|body| body.entity);
+        let contacts = world.get_resource::<Contacts2D>().cloned().unwrap_or_default();
+        let mut joints: Vec<(Entity, Joint2D)> = world.query::<(Entity, &Joint2D)>().iter(world)
+            .map(|(entity, joint)| (entity, *joint))
+            .collect();
+        joints.sort_unstable_by_key(|(entity, _)| *entity);
+        let mut colliding: Vec<(Entity, CollidingEntities)> = world.query::<(Entity, &CollidingEntities)>().iter(world)
+            .map(|(entity, colliding)| (entity, colliding.clone()))
+            .collect();
+        colliding.sort_unstable_by_key(|(entity, _)| *entity);
+        Self{
+            elapsed: clock.elapsed,
+            steps: clock.steps,
+            accumulated: world.get_resource::<FixedTime>().map(|fixed_time| fixed_time.accumulated()).unwrap_or_default(),
+            next_step_size,
+            bodies,
+            contacts,
+            joints,
+            touching: world.get_resource::<TouchingPairs2D>().cloned().unwrap_or_default(),
+            joint_bodies: world.get_resource::<JointBodies2D>().cloned().unwrap_or_default(),
+            islands: world.get_resource::<SimulationIslands>().cloned().unwrap_or_default(),
+            colliding
+        }
+    }
+    //Writes the snapshot back into the world. Transforms follow on the next interpolate_transforms.
+    //Bodies and joints spawned after the capture are left as they are, despawn them first for an exact replay.
+    //Returns the entities of the snapshot that no longer have SimulationData and were skipped
+    pub fn restore(&self, world: &mut World) -> Vec<Entity>{
+        if let Some(mut clock) = world.get_resource_mut::<SimulationClock>() {
+            clock.elapsed = self.elapsed;
+            clock.steps = self.steps;
+        }
+        if let Some(mut fixed_time) = world.get_resource_mut::<FixedTime>() {
+            //the accumulator can only be added to, so start over from an empty one with the same period
+            let period = fixed_time.period;
+            *fixed_time = FixedTime::new(period);
+            fixed_time.tick(self.accumulated);
+        }
+        if let Some(mut workspace) = world.get_resource_mut::<SolverWorkspace>() {
+            workspace.next_step_size = self.next_step_size;
+        }
+        world.insert_resource(self.touching.clone());
+        world.insert_resource(self.joint_bodies.clone());
+        world.insert_resource(self.islands.clone());
+        let mut colliding = world.query::<&mut CollidingEntities>();
+        for (entity, snapshot) in self.colliding.iter(){
+            if let Ok(mut component) = colliding.get_mut(world, *entity) {
+                *component = snapshot.clone();
+            }
+        }
+        if let Some(mut contacts) = world.get_resource_mut::<Contacts2D>() {
+            *contacts = self.contacts.clone();
+        }
+        let mut joints = world.query::<&mut Joint2D>();
+        for (entity, snapshot) in self.joints.iter(){
+            //past change detection, which would wake the bodies of the joint
+            if let Ok(mut joint) = joints.get_mut(world, *entity) {
+                *joint.bypass_change_detection() = *snapshot;
+            }
+        }
+        let mut query = world.query::<(&mut SimulationData, &mut RigidBody2D)>();
+        self.bodies.iter().filter_map(|snapshot| {
+            let Ok((mut data, mut body)) = query.get_mut(world, snapshot.entity) else {
+                return Some(snapshot.entity);
+            };
+            *data = snapshot.data.clone();
+            *body = snapshot.body.clone();
+            if snapshot.sleeping {
+                world.entity_mut(snapshot.entity).insert(Sleeping);
+            }else{
+                world.entity_mut(snapshot.entity).remove::<Sleeping>();
+            }
+            None
+        }).collect()
+    }
+}
+
+//Restores a FlatlandSnapshot from a regular system through Commands
+pub struct RestoreFlatlandSnapshot(pub FlatlandSnapshot);
+impl Command for RestoreFlatlandSnapshot{
+    fn apply(self, world: &mut World) {
+        let missing = self.0.restore(world);
+        if !missing.is_empty() {
+            warn!("{} bodies of the restored flatland snapshot no longer exist", missing.len());
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use super::*;
+
+//Single substep of a splitting method. 
+//Drift moves positions with the current velocities, Kick updates velocities with the accelerations at the current positions
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SplittingStep{
+    Drift(Scalar),
+    Kick(Scalar)
+}
+
+//Contains the config values for symplectic integrators, which alternate position and velocity updates instead of using stages.
+//These keep the energy of conservative systems bounded over long simulations, as long as the step size stays fixed
+#[derive(Resource, Clone, Debug)]
+pub struct SymplecticSolverConfig{
+    //Order of accuracy of the method
+    order: usize,
+    //Drift and kick coefficients applied in sequence, the drift and kick coefficients each sum to 1
+    steps: Vec<SplittingStep>
+}
+impl SymplecticSolverConfig{
+//Constructors:
+
+    pub fn semi_implicit_euler() -> Self{
+        Self{
+            order: 1,
+            steps: vec![SplittingStep::Kick(1.0), SplittingStep::Drift(1.0)]
+        }
+    }
+    pub fn velocity_verlet() -> Self{
+        Self{
+            order: 2,
+            steps: vec![SplittingStep::Kick(0.5), SplittingStep::Drift(1.0), SplittingStep::Kick(0.5)]
+        }
+    }
+    pub fn leapfrog() -> Self{
+        Self{
+            order: 2,
+            steps: vec![SplittingStep::Drift(0.5), SplittingStep::Kick(1.0), SplittingStep::Drift(0.5)]
+        }
+    }
+    //Yoshida's fourth order composition of leapfrog, also known as the Forest-Ruth method
+    pub fn forest_ruth() -> Self{
+        let cbrt2 = (2.0 as Scalar).cbrt();
+        let w1 = 1.0/(2.0 - cbrt2);
+        let w0 = -cbrt2/(2.0 - cbrt2);
+        Self{
+            order: 4,
+            steps: vec![
+                SplittingStep::Drift(w1/2.0),
+                SplittingStep::Kick(w1),
+                SplittingStep::Drift((w0 + w1)/2.0),
+                SplittingStep::Kick(w0),
+                SplittingStep::Drift((w0 + w1)/2.0),
+                SplittingStep::Kick(w1),
+                SplittingStep::Drift(w1/2.0)
+            ]
+        }
+    }
+    pub fn yoshida4() -> Self{
+        Self::forest_ruth()
+    }
+
+//Solver:
+
+    pub fn order(&self) -> usize{
+        self.order
+    }
+
+    //Advances every state in <states> by <dt>. Only the acceleration (velocity slots) of the derivative is used,
+    //so forces that depend on velocity are evaluated with the velocity of the previous kick
+    pub fn step<F>(
+        &self, 
+        t: Scalar, 
+        dt: Scalar, 
+        states: &mut [RigidBodyState2D], 
+        workspace: &mut SolverWorkspace, 
+        mut derivative: F
+    ) where F: FnMut(Scalar, &[RigidBodyState2D], &mut [RigidBodyState2D]) {
+        workspace.resize(1, states.len());
+        let mut time = t;
+        for step in self.steps.iter(){
+            match *step {
+                SplittingStep::Drift(c) => {
+                    for state in states.iter_mut(){
+                        state.position += state.velocity*(c*dt);
+                        state.angle += state.angular_velocity*(c*dt);
+                    }
+                    time += c*dt;
+                },
+                SplittingStep::Kick(d) => {
+                    derivative(time, states, &mut workspace.stages[0]);
+                    for (state, rate) in states.iter_mut().zip(workspace.stages[0].iter()){
+                        state.velocity += rate.velocity*(d*dt);
+                        state.angular_velocity += rate.angular_velocity*(d*dt);
+                    }
+                }
+            }
+        }
+    }
+}
+impl Default for SymplecticSolverConfig{
+    fn default() -> Self {
+        Self::velocity_verlet()
+    }
+}
+
+//Selects which family of integrators UpdateRungeKutta advances the simulation with
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SolverMode{
+    //Runge-Kutta tableau from DiffEqSolverConfig, explicit or implicit, adaptive if the tableau is embedded
+    #[default]
+    RungeKutta,
+    //Splitting method from SymplecticSolverConfig
+    Symplectic
+}
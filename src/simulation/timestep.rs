@@ -0,0 +1,86 @@
+use std::time::Duration;
+use super::*;
+
+//Fixed timestep the flatland simulation advances by, independent of the frame rate
+#[derive(Resource, Clone, Debug)]
+pub struct FlatlandTimestep{
+    //Length of a single simulation step in seconds
+    pub step: Scalar,
+    //Maximum number of simulation steps per frame. 
+    //Time beyond this is dropped, so long frames slow the simulation down instead of making the next frame even longer
+    pub max_substeps: u32
+}
+impl Default for FlatlandTimestep{
+    fn default() -> Self {
+        Self{step: 1.0/60.0, max_substeps: 8}
+    }
+}
+
+//Simulated time, which falls behind real time whenever steps are dropped
+#[derive(Resource, Clone, Debug, Default)]
+pub struct SimulationClock{
+    pub elapsed: Scalar,
+    pub steps: u64,
+    //steps left this frame before max_substeps is reached
+    remaining_substeps: u32
+}
+
+//System sets of a single flatland simulation step, run in FixedUpdate
+#[derive(SystemSet, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum FlatlandSet{
+    //Parent of every other set, skipped once the frame runs out of substeps
+    Step,
+    //Advances the clock and wakes disturbed bodies
+    Prepare,
+    //Runs the differential equation solver
+    Integrate,
+    //Finds contacts between colliders at the new state
+    Collide,
+    //Resolves the contacts found and the joints, then puts resting islands to sleep
+    Resolve,
+    //Measures the state after the step
+    Diagnostics
+}
+
+//Keeps FixedTime in sync with FlatlandTimestep and refills the substep budget for the frame
+pub fn sync_fixed_timestep(
+    timestep: Res<FlatlandTimestep>,
+    mut fixed_time: ResMut<FixedTime>,
+    mut clock: ResMut<SimulationClock>
+){
+    let period = Duration::from_secs_f64(to_f64(timestep.step));
+    if fixed_time.period != period {
+        fixed_time.period = period;
+    }
+    clock.remaining_substeps = timestep.max_substeps;
+}
+
+pub fn has_substeps_remaining(clock: Res<SimulationClock>) -> bool{
+    clock.remaining_substeps > 0
+}
+
+pub fn advance_simulation_clock(
+    timestep: Res<FlatlandTimestep>,
+    mut clock: ResMut<SimulationClock>
+){
+    clock.remaining_substeps -= 1;
+    clock.elapsed += timestep.step;
+    clock.steps += 1;
+}
+
+//Places every body between its last two simulated states, by how far real time has progressed towards the next step
+pub fn interpolate_transforms(
+    fixed_time: Res<FixedTime>,
+    mut bodies: Query<(&mut Transform, &SimulationData, Option<&RigidBody2D>)>
+){
+    let alpha = (fixed_time.accumulated().as_secs_f64()/fixed_time.period.as_secs_f64()).clamp(0.0, 1.0) as Scalar;
+    for (mut transform, data, body) in bodies.iter_mut(){
+        let mut state = data.previous + (data.state - data.previous)*alpha;
+        //the simulation follows the centre of mass, the transform sits at the body's origin
+        if let Some(body) = body {
+            state = state.shifted(-body.center_of_mass);
+        }
+        transform.translation = to_vec2(state.position).extend(transform.translation.z);
+        transform.rotation = Quat::from_rotation_z(to_f32(state.angle));
+    }
+}
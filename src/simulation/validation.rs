@@ -0,0 +1,219 @@
+use std::fmt;
+use super::*;
+
+//Largest deviation allowed when checking the consistency and order conditions of a tableau
+pub const TABLEAU_TOLERANCE: Scalar = 1e-5;
+
+//Reasons a user supplied Butcher tableau can be rejected
+#[derive(Clone, Debug, PartialEq)]
+pub enum TableauError{
+    //The tableau has no stages
+    Empty,
+    //A vector or matrix row doesn't have one entry per stage
+    DimensionMismatch{field: &'static str, expected: usize, found: usize},
+    //A coefficient is NaN or infinite
+    NonFinite{field: &'static str},
+    //c_i doesn't equal the sum of row i of the coefficient matrix
+    RowSumMismatch{stage: usize, node: Scalar, row_sum: Scalar},
+    //The weights don't sum to 1, so the method isn't consistent
+    WeightSum{field: &'static str, sum: Scalar},
+    //A parameterised family was given a value where its weights are undefined
+    InvalidParameter{name: &'static str, value: Scalar},
+    //The weights only satisfy the order conditions up to <satisfied>, below the <declared> order of accuracy
+    OrderConditions{field: &'static str, declared: usize, satisfied: usize}
+}
+impl fmt::Display for TableauError{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "tableau has no stages"),
+            Self::DimensionMismatch{field, expected, found} => 
+                write!(f, "{field} has {found} entries, expected {expected}"),
+            Self::NonFinite{field} => write!(f, "{field} contains a NaN or infinite coefficient"),
+            Self::RowSumMismatch{stage, node, row_sum} => 
+                write!(f, "node {stage} is {node} but row {stage} of the coefficient matrix sums to {row_sum}"),
+            Self::WeightSum{field, sum} => write!(f, "{field} sum to {sum} instead of 1"),
+            Self::InvalidParameter{name, value} => write!(f, "{name} = {value} is outside the family of valid methods"),
+            Self::OrderConditions{field, declared, satisfied} => 
+                write!(f, "{field} only satisfy the order conditions up to order {satisfied}, but order {declared} was declared")
+        }
+    }
+}
+impl std::error::Error for TableauError{}
+
+impl DiffEqSolverConfig{
+//Validated Constructors:
+
+    //Builds a config from an arbitrary tableau, checking its dimensions, consistency and that it satisfies the order conditions 
+    //of the declared <accuracy_order>. Tableaus with any coefficient on or above the diagonal are treated as implicit and solved with the default StageSolver
+    pub fn from_tableau(nodes: Vec<Scalar>, weights: Vec<Scalar>, coeff_matrix: Vec<Vec<Scalar>>, accuracy_order: usize) -> Result<Self, TableauError>{
+        let order = nodes.len();
+        if order == 0 {
+            return Err(TableauError::Empty);
+        }
+        check_length("weights", order, weights.len())?;
+        check_length("coeff_matrix", order, coeff_matrix.len())?;
+        for row in coeff_matrix.iter(){
+            check_length("coeff_matrix row", order, row.len())?;
+        }
+        check_finite("nodes", &nodes)?;
+        check_finite("weights", &weights)?;
+        for row in coeff_matrix.iter(){
+            check_finite("coeff_matrix", row)?;
+        }
+        for (stage, (node, row)) in nodes.iter().zip(coeff_matrix.iter()).enumerate(){
+            let row_sum: Scalar = row.iter().sum();
+            if (row_sum - node).abs() > TABLEAU_TOLERANCE {
+                return Err(TableauError::RowSumMismatch{stage, node: *node, row_sum});
+            }
+        }
+        check_weight_sum("weights", &weights)?;
+        check_order_conditions("weights", &weights, &coeff_matrix, accuracy_order)?;
+        let implicit = coeff_matrix.iter().enumerate().any(|(i, row)| row[i..].iter().any(|a| *a != 0.0));
+        Ok(Self{
+            order,
+            nodes,
+            weights,
+            coeff_matrix,
+            stage_solver: if implicit { Some(StageSolver::default()) } else { None },
+            embedded: None
+        })
+    }
+    //Builds an embedded pair from an arbitrary tableau. <error_order> is the order of accuracy of the less accurate solution,
+    //which both sets of weights have to satisfy
+    pub fn from_embedded_tableau(
+        nodes: Vec<Scalar>, 
+        weights: Vec<Scalar>, 
+        coeff_matrix: Vec<Vec<Scalar>>, 
+        accuracy_order: usize,
+        embedded_weights: Vec<Scalar>, 
+        error_order: usize
+    ) -> Result<Self, TableauError>{
+        //the step size control needs an error estimate that shrinks with the step
+        if error_order == 0 {
+            return Err(TableauError::InvalidParameter{name: "error_order", value: 0.0});
+        }
+        let mut config = Self::from_tableau(nodes, weights, coeff_matrix, accuracy_order.max(error_order))?;
+        check_length("embedded_weights", config.order, embedded_weights.len())?;
+        check_finite("embedded_weights", &embedded_weights)?;
+        check_weight_sum("embedded_weights", &embedded_weights)?;
+        check_order_conditions("embedded_weights", &embedded_weights, &config.coeff_matrix, error_order)?;
+        config.embedded = Some(EmbeddedWeights{weights: embedded_weights, error_order});
+        Ok(config)
+    }
+    pub fn try_second_order(a: Scalar) -> Result<Self, TableauError>{
+        if a == 0.0 || !a.is_finite() {
+            return Err(TableauError::InvalidParameter{name: "a", value: a});
+        }
+        Ok(Self::second_order(a))
+    }
+    pub fn try_third_order(a: Scalar) -> Result<Self, TableauError>{
+        //the weights blow up near the excluded values, not only on them
+        if [0.0, 1.0, 2.0/3.0].iter().any(|excluded| (a - excluded).abs() <= TABLEAU_TOLERANCE) || !a.is_finite() {
+            return Err(TableauError::InvalidParameter{name: "a", value: a});
+        }
+        Ok(Self::third_order(a))
+    }
+
+//Accessors:
+
+    pub fn order(&self) -> usize{
+        self.order
+    }
+    pub fn nodes(&self) -> &[Scalar]{
+        &self.nodes
+    }
+    pub fn weights(&self) -> &[Scalar]{
+        &self.weights
+    }
+    pub fn coeff_matrix(&self) -> &[Vec<Scalar>]{
+        &self.coeff_matrix
+    }
+    pub fn embedded_weights(&self) -> Option<&[Scalar]>{
+        self.embedded.as_ref().map(|embedded| embedded.weights.as_slice())
+    }
+
+    //Highest order of accuracy whose order conditions are all satisfied by the propagated weights, 
+    //bounded by the most an explicit or implicit tableau with this many stages can reach.
+    //Assumes the row sum condition holds, which every constructor guarantees
+    pub fn accuracy_order(&self) -> usize{
+        let bound = if self.is_implicit() { 2*self.order } else { self.order };
+        satisfied_order(&self.weights, &self.coeff_matrix, bound)
+    }
+    //Order of accuracy of the embedded solution, bounded like accuracy_order
+    pub fn embedded_accuracy_order(&self) -> Option<usize>{
+        let bound = if self.is_implicit() { 2*self.order } else { self.order };
+        self.embedded.as_ref().map(|embedded| satisfied_order(&embedded.weights, &self.coeff_matrix, bound))
+    }
+}
+
+fn check_length(field: &'static str, expected: usize, found: usize) -> Result<(), TableauError>{
+    if expected != found {
+        return Err(TableauError::DimensionMismatch{field, expected, found});
+    }
+    Ok(())
+}
+fn check_finite(field: &'static str, values: &[Scalar]) -> Result<(), TableauError>{
+    if values.iter().any(|v| !v.is_finite()) {
+        return Err(TableauError::NonFinite{field});
+    }
+    Ok(())
+}
+fn check_weight_sum(field: &'static str, weights: &[Scalar]) -> Result<(), TableauError>{
+    let sum: Scalar = weights.iter().sum();
+    if (sum - 1.0).abs() > TABLEAU_TOLERANCE {
+        return Err(TableauError::WeightSum{field, sum});
+    }
+    Ok(())
+}
+
+fn check_order_conditions(field: &'static str, weights: &[Scalar], coeff_matrix: &[Vec<Scalar>], declared: usize) -> Result<(), TableauError>{
+    let satisfied = satisfied_order(weights, coeff_matrix, declared);
+    if satisfied < declared {
+        return Err(TableauError::OrderConditions{field, declared, satisfied});
+    }
+    Ok(())
+}
+
+//Highest order up to <max_order> whose order conditions are all satisfied by the weights <b>.
+//There is one condition per rooted tree t, sum_i(b_i * g_i(t)) = 1/density(t), where g_i is 1 for the single node tree
+//and otherwise the product over the subtrees u hanging from the root of sum_j(a_ij * g_j(u)).
+//The density of a tree is its number of nodes times the densities of its subtrees
+fn satisfied_order(b: &[Scalar], a: &[Vec<Scalar>], max_order: usize) -> usize{
+    let s = b.len();
+    //every tree of the orders checked so far, as its order, sum_j(a_ij * g_j) for every row and its density
+    let mut trees: Vec<(usize, Vec<Scalar>, Scalar)> = Vec::new();
+    for order in 1..=max_order{
+        let orders: Vec<usize> = trees.iter().map(|(order, _, _)| *order).collect();
+        //the trees of this order are the multisets of smaller trees whose orders add up to one less
+        let mut subtrees = Vec::new();
+        tree_multisets(&orders, order - 1, 0, &mut Vec::new(), &mut subtrees);
+        let mut new_trees = Vec::with_capacity(subtrees.len());
+        for children in subtrees{
+            let g: Vec<Scalar> = (0..s).map(|i| children.iter().map(|child| trees[*child].1[i]).product()).collect();
+            let density = order as Scalar*children.iter().map(|child| trees[*child].2).product::<Scalar>();
+            let value: Scalar = b.iter().zip(g.iter()).map(|(b, g)| b*g).sum();
+            if (value - 1.0/density).abs() > TABLEAU_TOLERANCE {
+                return order - 1;
+            }
+            let ag: Vec<Scalar> = (0..s).map(|i| (0..s).map(|j| a[i][j]*g[j]).sum()).collect();
+            new_trees.push((order, ag, density));
+        }
+        trees.extend(new_trees);
+    }
+    max_order
+}
+
+//Collects every non-decreasing list of indices into <orders> whose orders add up to <remaining>
+fn tree_multisets(orders: &[usize], remaining: usize, first: usize, current: &mut Vec<usize>, sets: &mut Vec<Vec<usize>>){
+    if remaining == 0 {
+        sets.push(current.clone());
+        return;
+    }
+    for index in first..orders.len(){
+        if orders[index] <= remaining {
+            current.push(index);
+            tree_multisets(orders, remaining - orders[index], index, current, sets);
+            current.pop();
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use bevy_flatland::scalar::*;
+use bevy_flatland::simulation::*;
+
+const OMEGA: Scalar = 20.0;
+
+//Harmonic oscillator in x with angular frequency OMEGA, next to a clock whose x integrates time exactly,
+//so every test can check that a frame was covered whatever substeps it took
+fn oscillator(_: Scalar, states: &[RigidBodyState2D], out: &mut [RigidBodyState2D]){
+    out[0] = RigidBodyState2D{
+        position: Vector::new(states[0].velocity.x, 0.0),
+        velocity: Vector::new(-OMEGA*OMEGA*states[0].position.x, 0.0),
+        ..Default::default()
+    };
+    out[1] = RigidBodyState2D{position: Vector::new(1.0, 0.0), ..Default::default()};
+}
+
+fn initial() -> [RigidBodyState2D; 2]{
+    [RigidBodyState2D{position: Vector::new(1.0, 0.0), ..Default::default()}, RigidBodyState2D::default()]
+}
+
+fn tolerance() -> AdaptiveStepConfig{
+    AdaptiveStepConfig{absolute_tolerance: 1e-5, relative_tolerance: 1e-5, ..Default::default()}
+}
+
+#[test]
+fn embedded_pairs_reject_steps_out_of_tolerance_and_report_them(){
+    let config = DiffEqSolverConfig::dormand_prince();
+    let (mut states, mut workspace, frame) = (initial(), SolverWorkspace::default(), 0.5);
+    //the first try covers the whole frame, ten radians of oscillation
+    let report = config.advance(0.0, frame, &mut states, &mut workspace, &tolerance(), oscillator);
+    assert!(report.rejected_steps >= 1, "{report:?}");
+    assert!(report.accepted_steps > 1, "{report:?}");
+    assert!(report.max_error > 0.0 && report.max_error <= 1.0, "{report:?}");
+    assert!(report.last_step_size > 0.0 && report.last_step_size < frame, "{report:?}");
+    assert!((states[1].position.x - frame).abs() < 1e-5, "{states:?}");
+    assert!((states[0].position.x - (OMEGA*frame).cos()).abs() < 1e-3, "{states:?}");
+}
+
+#[test]
+fn the_step_size_carries_over_to_the_next_frame(){
+    let config = DiffEqSolverConfig::dormand_prince();
+    let (mut states, mut workspace, frame) = (initial(), SolverWorkspace::default(), 0.5);
+    let first = config.advance(0.0, frame, &mut states, &mut workspace, &tolerance(), oscillator);
+    let (mut fresh_states, mut fresh_workspace) = (states, SolverWorkspace::default());
+    let second = config.advance(frame, frame, &mut states, &mut workspace, &tolerance(), oscillator);
+    //starting the frame over from its full length has to find the step size again
+    let fresh = config.advance(frame, frame, &mut fresh_states, &mut fresh_workspace, &tolerance(), oscillator);
+    assert!(second.rejected_steps < first.rejected_steps, "{first:?} {second:?}");
+    assert!(second.rejected_steps < fresh.rejected_steps, "{second:?} {fresh:?}");
+    assert!((states[1].position.x - 2.0*frame).abs() < 1e-5, "{states:?}");
+    assert!((states[0].position.x - (2.0*OMEGA*frame).cos()).abs() < 1e-3, "{states:?}");
+}
+
+#[test]
+fn the_last_allowed_substep_finishes_the_frame(){
+    let config = DiffEqSolverConfig::dormand_prince();
+    let (mut states, mut workspace, frame) = (initial(), SolverWorkspace::default(), 0.5);
+    let adaptive = AdaptiveStepConfig{max_substeps: 3, ..tolerance()};
+    let report = config.advance(0.0, frame, &mut states, &mut workspace, &adaptive, oscillator);
+    assert_eq!(report.accepted_steps + report.rejected_steps, 3, "{report:?}");
+    //forced out of tolerance to get there
+    assert!(report.max_error > 1.0, "{report:?}");
+    assert!((states[1].position.x - frame).abs() < 1e-5, "{states:?}");
+}
+
+#[test]
+fn steps_that_blow_up_are_retried_smaller(){
+    let config = DiffEqSolverConfig::dormand_prince();
+    let (mut states, mut workspace, frame) = (initial(), SolverWorkspace::default(), 0.5);
+    //stops giving numbers once the stages overshoot, as a simulation that blew up would
+    let decay = |_: Scalar, states: &[RigidBodyState2D], out: &mut [RigidBodyState2D]| {
+        let x = states[0].position.x;
+        let rate = if x.abs() > 2.0 { Scalar::NAN } else { -50.0*x };
+        out[0] = RigidBodyState2D{position: Vector::new(rate, 0.0), ..Default::default()};
+        out[1] = RigidBodyState2D{position: Vector::new(1.0, 0.0), ..Default::default()};
+    };
+    let report = config.advance(0.0, frame, &mut states, &mut workspace, &tolerance(), decay);
+    assert!(report.rejected_steps >= 1, "{report:?}");
+    assert!(report.max_error <= 1.0, "{report:?}");
+    assert!(states[0].position.x.is_finite() && (states[0].position.x - (-50.0*frame).exp()).abs() < 1e-3, "{states:?}");
+    assert!((states[1].position.x - frame).abs() < 1e-5, "{states:?}");
+}
@@ -0,0 +1,120 @@
+use bevy_flatland::collision::*;
+use bevy_flatland::components::BoxCollider2D;
+use bevy_flatland::scalar::*;
+
+const TOLERANCE: Scalar = 1e-4;
+
+fn unit_box() -> BoxCollider2D{
+    BoxCollider2D{center_position: Vector::ZERO, half_extents: Vector::splat(0.5), restitution: 0.0, friction: 0.0}
+}
+
+fn ground() -> BoxCollider2D{
+    BoxCollider2D{half_extents: Vector::new(5.0, 0.5), ..unit_box()}
+}
+
+fn at(x: Scalar, y: Scalar, angle: Scalar) -> Isometry2D{
+    Isometry2D::new(Vector::new(x, y), angle)
+}
+
+fn sorted_xs(manifold: &ContactManifold2D) -> Vec<Scalar>{
+    let mut xs: Vec<Scalar> = manifold.points.iter().map(|point| point.point.x).collect();
+    xs.sort_unstable_by(Scalar::total_cmp);
+    xs
+}
+
+#[test]
+fn resting_box_gets_two_clipped_points(){
+    let manifold = collide_boxes(&ground(), at(0.0, 0.0, 0.0), &unit_box(), at(1.0, 0.9, 0.0)).unwrap();
+    assert!((manifold.normal - Vector::Y).length() < TOLERANCE, "{manifold:?}");
+    assert_eq!(manifold.points.len(), 2);
+    for point in manifold.points.iter(){
+        assert!((point.depth - 0.1).abs() < TOLERANCE, "{point:?}");
+        //midway between the two surfaces
+        assert!((point.point.y - 0.45).abs() < TOLERANCE, "{point:?}");
+    }
+    let xs = sorted_xs(&manifold);
+    assert!((xs[0] - 0.5).abs() < TOLERANCE && (xs[1] - 1.5).abs() < TOLERANCE, "{xs:?}");
+    assert_ne!(manifold.points[0].id, manifold.points[1].id);
+}
+
+#[test]
+fn swapping_the_boxes_flips_the_normal(){
+    let manifold = collide_boxes(&ground(), at(0.0, 0.0, 0.0), &unit_box(), at(1.0, 0.9, 0.0)).unwrap();
+    let swapped = collide_boxes(&unit_box(), at(1.0, 0.9, 0.0), &ground(), at(0.0, 0.0, 0.0)).unwrap();
+    assert!((swapped.normal + manifold.normal).length() < TOLERANCE, "{swapped:?}");
+    assert_eq!(sorted_xs(&swapped).len(), 2);
+    for (point, swapped_point) in sorted_xs(&manifold).iter().zip(sorted_xs(&swapped)){
+        assert!((point - swapped_point).abs() < TOLERANCE);
+    }
+}
+
+#[test]
+fn separated_boxes_dont_collide(){
+    assert!(collide_boxes(&ground(), at(0.0, 0.0, 0.0), &unit_box(), at(1.0, 1.01, 0.0)).is_none());
+    assert!(collide_boxes(&ground(), at(0.0, 0.0, 0.0), &unit_box(), at(5.51, 0.9, 0.0)).is_none());
+    //the corner of a rotated box just clear of the face
+    let corner = 0.5*(2.0 as Scalar).sqrt();
+    assert!(collide_boxes(&ground(), at(0.0, 0.0, 0.0), &unit_box(), at(0.0, 0.5 + corner + 0.01, PI/4.0)).is_none());
+}
+
+#[test]
+fn rotated_box_touches_at_its_corner(){
+    let corner = 0.5*(2.0 as Scalar).sqrt();
+    let manifold = collide_boxes(&ground(), at(0.0, 0.0, 0.0), &unit_box(), at(0.0, 0.5 + corner - 0.1, PI/4.0)).unwrap();
+    assert!((manifold.normal - Vector::Y).length() < TOLERANCE, "{manifold:?}");
+    assert_eq!(manifold.points.len(), 1, "{manifold:?}");
+    let point = manifold.points[0];
+    assert!((point.depth - 0.1).abs() < TOLERANCE, "{point:?}");
+    assert!(point.point.x.abs() < TOLERANCE && (point.point.y - 0.45).abs() < TOLERANCE, "{point:?}");
+}
+
+#[test]
+fn overhanging_box_is_clipped_to_the_reference_face(){
+    let manifold = collide_boxes(&ground(), at(0.0, 0.0, 0.0), &unit_box(), at(5.3, 0.9, 0.0)).unwrap();
+    assert!((manifold.normal - Vector::Y).length() < TOLERANCE, "{manifold:?}");
+    let xs = sorted_xs(&manifold);
+    assert_eq!(xs.len(), 2);
+    assert!((xs[0] - 4.8).abs() < TOLERANCE && (xs[1] - 5.0).abs() < TOLERANCE, "{xs:?}");
+}
+
+#[test]
+fn point_ids_survive_small_motions(){
+    let ids = |x: Scalar, y: Scalar, angle: Scalar| {
+        let mut ids: Vec<u32> = collide_boxes(&ground(), at(0.0, 0.0, 0.0), &unit_box(), at(x, y, angle)).unwrap()
+            .points.iter().map(|point| point.id).collect();
+        ids.sort_unstable();
+        ids
+    };
+    let resting = ids(1.0, 0.9, 0.0);
+    assert_eq!(ids(1.02, 0.91, 0.01), resting);
+    assert_eq!(ids(0.97, 0.89, -0.01), resting);
+}
+
+#[test]
+fn warm_starting_copies_impulses_of_matching_points(){
+    let mut last = collide_boxes(&ground(), at(0.0, 0.0, 0.0), &unit_box(), at(1.0, 0.9, 0.0)).unwrap();
+    for (index, point) in last.points.iter_mut().enumerate(){
+        point.normal_impulse = 1.0 + index as Scalar;
+        point.tangent_impulse = -0.5;
+    }
+    let mut next = collide_boxes(&ground(), at(0.0, 0.0, 0.0), &unit_box(), at(1.01, 0.9, 0.0)).unwrap();
+    next.warm_start_from(&last);
+    for point in next.points.iter(){
+        let matching = last.points.iter().find(|matching| matching.id == point.id).unwrap();
+        assert_eq!((point.normal_impulse, point.tangent_impulse), (matching.normal_impulse, matching.tangent_impulse));
+    }
+}
+
+#[test]
+fn incident_edges_ending_on_a_side_plane_keep_only_the_inside_end(){
+    let ground = ConvexShape2D::new(vec![Vector::new(-1.0, -0.5), Vector::new(1.0, -0.5), Vector::new(1.0, 0.5), Vector::new(-1.0, 0.5)], 0.0);
+    //its flat bottom starts right above the ground's corner and runs past it
+    let ledge = ConvexShape2D::new(vec![
+        Vector::new(1.0, 0.45), Vector::new(3.0, 0.45), Vector::new(3.0, 2.0), Vector::new(-3.0, 2.0), Vector::new(-3.0, 0.6)
+    ], 0.0);
+    let manifold = collide_convex(&ground, &ledge).unwrap();
+    assert!((manifold.normal - Vector::Y).length() < TOLERANCE, "{manifold:?}");
+    assert_eq!(manifold.points.len(), 1, "{manifold:?}");
+    let point = manifold.points[0];
+    assert!((point.point.x - 1.0).abs() < TOLERANCE && (point.depth - 0.05).abs() < TOLERANCE, "{point:?}");
+}
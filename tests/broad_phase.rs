@@ -0,0 +1,124 @@
+use bevy::prelude::*;
+use bevy_flatland::collision::*;
+use bevy_flatland::components::{CollisionLayers2D, RigidBodyType2D};
+use bevy_flatland::scalar::*;
+
+//Small deterministic generator, so failures reproduce
+struct Lcg(u64);
+impl Lcg{
+    fn next(&mut self) -> Scalar{
+        self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (self.0 >> 40) as Scalar/(1u64 << 24) as Scalar
+    }
+}
+
+fn proxy(index: u32, center: Vector, half_extents: Vector, body: Option<u32>, polyline: bool, layers: CollisionLayers2D) -> ColliderProxy2D{
+    ColliderProxy2D{
+        entity: Entity::from_raw(index),
+        body: body.map(Entity::from_raw),
+        body_type: if body.is_some() { RigidBodyType2D::Dynamic } else { RigidBodyType2D::Static },
+        pose: Isometry2D::new(center, 0.0),
+        aabb: Aabb2D::new(center - half_extents, center + half_extents),
+        polyline,
+        layers,
+        sensor: false,
+        sleeping: false
+    }
+}
+
+//Colliders scattered over a field, some sharing a body, some static polylines, some kinematic and some on their own layer
+fn scatter(random: &mut Lcg, count: u32, field: Scalar) -> Vec<ColliderProxy2D>{
+    (0..count).map(|index| {
+        let center = Vector::new(random.next(), random.next())*field;
+        let half_extents = Vector::new(0.2 + random.next(), 0.2 + random.next());
+        let body = match index % 7 {
+            0 => None,
+            1 => Some(index - 1),
+            _ => Some(index)
+        };
+        let layers = if index % 5 == 0 { CollisionLayers2D::new(2, 2) } else { CollisionLayers2D::new(1, 1) };
+        let mut proxy = proxy(index, center, half_extents, body, index % 11 == 0 && body.is_none(), layers);
+        if index % 13 == 3 {
+            proxy.body_type = RigidBodyType2D::KinematicPositionBased;
+        }
+        proxy
+    }).collect()
+}
+
+fn brute_force_pairs(proxies: &[ColliderProxy2D]) -> Vec<(Entity, Entity)>{
+    let mut pairs = Vec::new();
+    for (index, a) in proxies.iter().enumerate(){
+        for b in proxies[index + 1..].iter(){
+            if a.aabb.overlaps(&b.aabb) && a.can_collide(b) {
+                pairs.push((a.entity.min(b.entity), a.entity.max(b.entity)));
+            }
+        }
+    }
+    pairs.sort_unstable();
+    pairs
+}
+
+#[test]
+fn pairs_match_brute_force_while_colliders_move(){
+    let mut random = Lcg(7);
+    let mut proxies = scatter(&mut random, 300, 40.0);
+    let mut broad_phase = BroadPhase2D::default();
+    let mut last = Vec::new();
+    for update in 0..20{
+        broad_phase.update(proxies.iter().copied());
+        let expected = brute_force_pairs(&proxies);
+        assert_eq!(broad_phase.pairs(), expected.as_slice(), "update {update}");
+        assert!(!expected.is_empty());
+        let stats = broad_phase.stats;
+        assert_eq!(stats.proxies, proxies.len());
+        assert_eq!(stats.candidate_pairs, expected.len());
+        assert_eq!(stats.started_pairs, expected.iter().filter(|pair| !last.contains(*pair)).count(), "update {update}");
+        assert_eq!(stats.ended_pairs, last.iter().filter(|pair| !expected.contains(*pair)).count(), "update {update}");
+        //sweeping along x skips most of the 300*299/2 pairs
+        assert!(stats.overlap_tests < proxies.len()*(proxies.len() - 1)/8, "{stats:?}");
+        for proxy in broad_phase.proxies(){
+            assert_eq!(broad_phase.proxy(proxy.entity).unwrap().entity, proxy.entity);
+        }
+        last = expected;
+
+        //jitter every collider, and replace a few with fresh ones
+        for proxy in proxies.iter_mut(){
+            let offset = (Vector::new(random.next(), random.next()) - Vector::splat(0.5))*0.6;
+            proxy.aabb = Aabb2D::new(proxy.aabb.min + offset, proxy.aabb.max + offset);
+        }
+        proxies.retain(|proxy| proxy.entity.index() % 20 != update);
+        proxies.extend(scatter(&mut random, 5, 40.0).into_iter().enumerate().map(|(index, mut proxy)| {
+            proxy.entity = Entity::from_raw(1000 + 5*update + index as u32);
+            proxy.body = proxy.body.map(|_| proxy.entity);
+            proxy
+        }));
+    }
+}
+
+#[test]
+fn bodies_body_types_polylines_and_layers_filter_pairs(){
+    let half = Vector::splat(1.0);
+    let layers = CollisionLayers2D::new(1, 1);
+    let proxies = [
+        proxy(0, Vector::ZERO, half, Some(10), false, layers),
+        //same body as 0
+        proxy(1, Vector::ZERO, half, Some(10), false, layers),
+        proxy(2, Vector::ZERO, half, None, true, layers),
+        proxy(3, Vector::ZERO, half, None, true, layers),
+        //in a group none of the others filter for
+        proxy(4, Vector::ZERO, half, Some(14), false, CollisionLayers2D::new(2, 2)),
+        //touching 0 to 3 at their edges only
+        proxy(5, Vector::new(2.0, 0.0), half, Some(15), false, layers),
+        //kinematic, so it only meets the dynamic bodies and the sensor
+        ColliderProxy2D{body_type: RigidBodyType2D::KinematicVelocityBased, ..proxy(6, Vector::ZERO, half, Some(16), false, layers)},
+        //a static sensor, which also reports the kinematic body
+        ColliderProxy2D{sensor: true, ..proxy(7, Vector::ZERO, half, None, false, layers)}
+    ];
+    let mut broad_phase = BroadPhase2D::default();
+    broad_phase.update(proxies);
+    let pair = |a: u32, b: u32| (Entity::from_raw(a), Entity::from_raw(b));
+    assert_eq!(broad_phase.pairs(), &[
+        pair(0, 2), pair(0, 3), pair(0, 5), pair(0, 6), pair(0, 7), pair(1, 2), pair(1, 3), pair(1, 5), pair(1, 6), pair(1, 7),
+        pair(2, 5), pair(3, 5), pair(5, 6), pair(5, 7), pair(6, 7)
+    ]);
+}
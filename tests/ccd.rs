@@ -0,0 +1,82 @@
+use bevy::{prelude::*, ecs::event::ManualEventReader};
+use bevy_flatland::collision::*;
+use bevy_flatland::components::*;
+use bevy_flatland::scalar::*;
+use bevy_flatland::simulation::*;
+mod common;
+use common::*;
+
+const WALL_HALF_THICKNESS: Scalar = 0.05;
+
+fn wall() -> BoxCollider2D{
+    BoxCollider2D{center_position: Vector::ZERO, half_extents: Vector::new(10.0, WALL_HALF_THICKNESS), restitution: 0.0, friction: 0.6}
+}
+
+fn sweep(from: Vector, to: Vector) -> Sweep2D{
+    Sweep2D{
+        start: RigidBodyState2D{position: from, ..Default::default()},
+        end: RigidBodyState2D{position: to, ..Default::default()},
+        center_of_mass: Vector::ZERO
+    }
+}
+
+#[test]
+fn time_of_impact_stops_short_of_the_wall(){
+    let circle = CircleCollider2D{center_position: Vector::ZERO, radius: 0.5, restitution: 0.0, friction: 0.0};
+    let target = 0.01;
+    let impact = time_of_impact(
+        Collider2D::Circle(&circle), Isometry2D::default(), &sweep(Vector::new(0.0, 5.0), Vector::new(0.0, -5.0)),
+        Collider2D::Box(&wall()), Isometry2D::default(), target
+    ).unwrap();
+    //the surfaces meet once the centre is a radius above the wall, 4.45 into the 10 long sweep
+    let contact_time = (5.0 - 0.5 - WALL_HALF_THICKNESS)/10.0;
+    assert!(impact.time <= contact_time && impact.time > contact_time - 2.0*target/10.0, "{impact:?}");
+    assert!(impact.separation >= 0.0 && impact.separation <= 1.25*target + 1e-4, "{impact:?}");
+    assert!((impact.normal - Vector::NEG_Y).length() < 1e-3, "{impact:?}");
+
+    //moving away from, or ending short of, the wall is no impact
+    assert!(time_of_impact(
+        Collider2D::Circle(&circle), Isometry2D::default(), &sweep(Vector::new(0.0, 1.0), Vector::new(0.0, 5.0)),
+        Collider2D::Box(&wall()), Isometry2D::default(), target
+    ).is_none());
+    assert!(time_of_impact(
+        Collider2D::Circle(&circle), Isometry2D::default(), &sweep(Vector::new(0.0, 5.0), Vector::new(0.0, 1.0)),
+        Collider2D::Box(&wall()), Isometry2D::default(), target
+    ).is_none());
+}
+
+//Fires a box at 300 units per second through a wall a tenth of a unit thick, returning its height after a second
+//and the impacts reported along the way
+fn fire_at_wall(continuous: bool) -> (Scalar, Vec<TimeOfImpact2D>, Entity, Entity){
+    let mut app = world();
+    let wall = app.world.spawn((Transform::default(), GlobalTransform::default(), wall())).id();
+    let bullet = spawn_box(&mut app, 0.0, 5.0, unit_box(0.0, 0.6));
+    app.world.get_mut::<SimulationData>(bullet).unwrap().state.velocity = Vector::new(0.0, -300.0);
+    if continuous {
+        app.world.entity_mut(bullet).insert(ContinuousCollision2D);
+    }
+    let mut reader = ManualEventReader::<TimeOfImpact2D>::default();
+    let mut impacts = Vec::new();
+    for _ in 0..60{
+        step(&mut app);
+        impacts.extend(reader.iter(app.world.resource::<Events<TimeOfImpact2D>>()).cloned());
+    }
+    (state(&app, bullet).position.y, impacts, bullet, wall)
+}
+
+#[test]
+fn fast_bodies_tunnel_without_continuous_collision(){
+    let (height, impacts, ..) = fire_at_wall(false);
+    assert!(height < -10.0, "stopped at {height}");
+    assert!(impacts.is_empty());
+}
+
+#[test]
+fn continuous_collision_stops_fast_bodies_at_thin_walls(){
+    let (height, impacts, bullet, wall) = fire_at_wall(true);
+    //resting on top of the wall, give or take the allowed penetration
+    assert!((height - (0.5 + WALL_HALF_THICKNESS)).abs() < 0.02, "ended at {height}");
+    let first = impacts.first().expect("no time of impact reported");
+    assert_eq!((first.body, first.collider, first.other), (bullet, bullet, wall));
+    assert!(first.impact.time > 0.0 && first.impact.time < 1.0, "{first:?}");
+}
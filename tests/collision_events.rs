@@ -0,0 +1,129 @@
+use bevy::{prelude::*, ecs::event::ManualEventReader};
+use bevy_flatland::collision::*;
+use bevy_flatland::components::*;
+use bevy_flatland::scalar::*;
+mod common;
+use common::*;
+
+#[derive(Default)]
+struct Recorder{
+    started: ManualEventReader<CollisionStarted>,
+    ended: ManualEventReader<CollisionEnded>,
+    //(step, a, b, started)
+    events: Vec<(usize, Entity, Entity, bool)>
+}
+impl Recorder{
+    fn step(&mut self, app: &mut App, step: usize){
+        common::step(app);
+        for event in self.started.iter(app.world.resource::<Events<CollisionStarted>>()){
+            assert!(!event.manifold.points.is_empty());
+            self.events.push((step, event.a, event.b, true));
+        }
+        for event in self.ended.iter(app.world.resource::<Events<CollisionEnded>>()){
+            self.events.push((step, event.a, event.b, false));
+        }
+    }
+    fn between(&self, a: Entity, b: Entity) -> Vec<(usize, bool)>{
+        self.events.iter()
+            .filter(|(_, first, second, _)| (*first, *second) == (a.min(b), a.max(b)))
+            .map(|(step, _, _, started)| (*step, *started))
+            .collect()
+    }
+}
+
+fn world_with_ground() -> (App, Entity){
+    let mut app = world();
+    let ground = spawn_ground(&mut app, 10.0, 0.0, 0.6);
+    app.world.entity_mut(ground).insert(CollidingEntities::default());
+    (app, ground)
+}
+
+fn spawn_reporting_box(app: &mut App, x: f32, y: f32, restitution: Scalar) -> Entity{
+    let body = spawn_box(app, x, y, unit_box(restitution, 0.6));
+    app.world.entity_mut(body).insert(CollidingEntities::default());
+    body
+}
+
+fn colliding(app: &App, entity: Entity) -> Vec<Entity>{
+    let mut entities: Vec<Entity> = app.world.get::<CollidingEntities>(entity).unwrap().0.iter().copied().collect();
+    entities.sort_unstable();
+    entities
+}
+
+#[test]
+fn landing_starts_a_collision_once(){
+    let (mut app, ground) = world_with_ground();
+    let resting = spawn_reporting_box(&mut app, 0.0, 3.0, 0.0);
+    let mut recorder = Recorder::default();
+    for step in 0..120{
+        recorder.step(&mut app, step);
+    }
+    let events = recorder.between(ground, resting);
+    assert_eq!(events.len(), 1, "{events:?}");
+    assert!(events[0].1);
+    assert_eq!(colliding(&app, ground), vec![resting]);
+    assert_eq!(colliding(&app, resting), vec![ground]);
+}
+
+#[test]
+fn bouncing_alternates_started_and_ended(){
+    let (mut app, ground) = world_with_ground();
+    let bouncing = spawn_reporting_box(&mut app, 0.0, 4.0, 0.8);
+    let mut recorder = Recorder::default();
+    let mut in_air_after_bounce = false;
+    //the first bounce keeps it in the air for over a second
+    for step in 0..150{
+        recorder.step(&mut app, step);
+        if recorder.between(ground, bouncing).len() == 2 && colliding(&app, ground).is_empty() {
+            in_air_after_bounce = true;
+        }
+    }
+    let events = recorder.between(ground, bouncing);
+    assert!(events.len() >= 3, "{events:?}");
+    for (index, (_, started)) in events.iter().enumerate(){
+        assert_eq!(*started, index % 2 == 0, "{events:?}");
+    }
+    assert!(in_air_after_bounce);
+}
+
+#[test]
+fn despawning_a_collider_ends_its_collisions(){
+    let (mut app, ground) = world_with_ground();
+    let stack: Vec<Entity> = (0..2).map(|level| spawn_reporting_box(&mut app, 0.0, 1.0 + level as f32, 0.0)).collect();
+    let mut recorder = Recorder::default();
+    for step in 0..30{
+        recorder.step(&mut app, step);
+    }
+    assert_eq!(colliding(&app, stack[0]), {
+        let mut expected = vec![ground, stack[1]];
+        expected.sort_unstable();
+        expected
+    });
+    app.world.despawn(stack[0]);
+    recorder.step(&mut app, 30);
+    assert_eq!(recorder.between(ground, stack[0]).last(), Some(&(30, false)));
+    assert_eq!(recorder.between(stack[0], stack[1]).last(), Some(&(30, false)));
+    assert!(!colliding(&app, ground).contains(&stack[0]));
+    assert!(!colliding(&app, stack[1]).contains(&stack[0]));
+    //the top box falls onto the ground in its place
+    for step in 31..90{
+        recorder.step(&mut app, step);
+    }
+    assert!(recorder.between(ground, stack[1]).first().is_some_and(|(step, started)| *started && *step > 30));
+    assert_eq!(colliding(&app, ground), vec![stack[1]]);
+}
+
+#[test]
+fn colliding_entities_added_later_start_with_current_contacts(){
+    let (mut app, ground) = world_with_ground();
+    let resting = spawn_reporting_box(&mut app, 0.0, 1.0, 0.0);
+    app.world.entity_mut(resting).remove::<CollidingEntities>();
+    let mut recorder = Recorder::default();
+    for step in 0..20{
+        recorder.step(&mut app, step);
+    }
+    app.world.entity_mut(resting).insert(CollidingEntities::default());
+    recorder.step(&mut app, 20);
+    assert_eq!(colliding(&app, resting), vec![ground]);
+    assert_eq!(recorder.between(ground, resting).len(), 1);
+}
@@ -0,0 +1,48 @@
+//World builders shared by the integration tests, each of which only uses some of them
+#![allow(dead_code)]
+use bevy::prelude::*;
+use bevy_flatland::components::*;
+use bevy_flatland::plugin::FlatlandPhysicsPlugin;
+use bevy_flatland::scalar::*;
+use bevy_flatland::simulation::*;
+
+pub fn world() -> App{
+    let mut app = App::new();
+    app.add_plugins(FlatlandPhysicsPlugin{});
+    app.init_resource::<Time>();
+    app
+}
+
+//Runs a frame that takes a single simulation step
+pub fn step(app: &mut App){
+    app.world.run_schedule(PreUpdate);
+    app.world.run_schedule(FixedUpdate);
+}
+
+pub fn state(app: &App, entity: Entity) -> RigidBodyState2D{
+    app.world.get::<SimulationData>(entity).unwrap().state
+}
+
+pub fn unit_box(restitution: Scalar, friction: Scalar) -> BoxCollider2D{
+    BoxCollider2D{center_position: Vector::ZERO, half_extents: Vector::splat(0.5), restitution, friction}
+}
+
+//Static box half a unit thick, with its top at y = 0.5
+pub fn spawn_ground(app: &mut App, half_width: Scalar, restitution: Scalar, friction: Scalar) -> Entity{
+    app.world.spawn((
+        Transform::default(), GlobalTransform::default(),
+        BoxCollider2D{center_position: Vector::ZERO, half_extents: Vector::new(half_width, 0.5), restitution, friction}
+    )).id()
+}
+
+//Dynamic body of unit mass, with the inertia of a unit box
+pub fn spawn_body(app: &mut App, x: f32, y: f32) -> Entity{
+    let transform = Transform::from_xyz(x, y, 0.0);
+    app.world.spawn((transform, GlobalTransform::from(transform), SimulationData::from_transform(&transform), RigidBody2D::new(1.0, 1.0/6.0))).id()
+}
+
+pub fn spawn_box(app: &mut App, x: f32, y: f32, collider: BoxCollider2D) -> Entity{
+    let body = spawn_body(app, x, y);
+    app.world.entity_mut(body).insert(collider);
+    body
+}
@@ -0,0 +1,63 @@
+use bevy::prelude::*;
+use bevy_flatland::collision::*;
+use bevy_flatland::scalar::*;
+mod common;
+use common::*;
+
+#[test]
+fn stack_comes_to_rest(){
+    let mut app = world();
+    spawn_ground(&mut app, 10.0, 0.0, 0.6);
+    //slightly staggered, so the stack has to settle rather than start balanced
+    let stack: Vec<Entity> = (0..5).map(|level| spawn_box(&mut app, 0.02*level as f32, 1.0 + level as f32, unit_box(0.0, 0.6))).collect();
+    for _ in 0..300{
+        step(&mut app);
+    }
+    let allowed_penetration = app.world.resource::<ContactSolverConfig>().allowed_penetration;
+    for (level, entity) in stack.iter().enumerate(){
+        let state = state(&app, *entity);
+        let rest_height = 1.0 + level as Scalar;
+        assert!((state.position.y - rest_height).abs() <= 2.0*(level + 1) as Scalar*allowed_penetration, "box {level} at {:?}", state.position);
+        assert!((state.position.x - 0.02*level as Scalar).abs() < 0.05, "box {level} slid to {:?}", state.position);
+        assert!(state.angle.abs() < 0.01, "box {level} tipped to {}", state.angle);
+        assert!(state.velocity.length() < 0.05 && state.angular_velocity.abs() < 0.05, "box {level} still moves: {state:?}");
+    }
+}
+
+//Vertical speed just before and just after the first bounce of a box dropped onto the ground
+fn first_bounce(ground_restitution: Scalar, box_restitution: Scalar) -> (Scalar, Scalar){
+    let mut app = world();
+    spawn_ground(&mut app, 10.0, ground_restitution, 0.6);
+    let entity = spawn_box(&mut app, 0.0, 4.0, unit_box(box_restitution, 0.6));
+    let mut falling = 0.0;
+    for _ in 0..120{
+        step(&mut app);
+        let velocity = state(&app, entity).velocity.y;
+        if velocity < 0.0 {
+            falling = velocity;
+        }else if falling < 0.0 {
+            return (falling, velocity);
+        }
+    }
+    (falling, 0.0)
+}
+
+#[test]
+fn restitution_scales_the_bounce(){
+    for restitution in [0.3, 0.8]{
+        let (falling, rising) = first_bounce(0.0, restitution);
+        assert!(falling < -5.0, "dropped box only reached {falling}");
+        //the step of impact also integrates gravity, which costs at most one step of velocity
+        assert!((rising/(-falling) - restitution).abs() < 0.05, "restitution {restitution} bounced {rising} after falling at {falling}");
+    }
+    //restitution combines by its maximum by default, so the ground's counts too
+    let (falling, rising) = first_bounce(0.8, 0.0);
+    assert!((rising/(-falling) - 0.8).abs() < 0.05, "bounced {rising} after falling at {falling}");
+}
+
+#[test]
+fn inelastic_boxes_dont_bounce(){
+    let (falling, rising) = first_bounce(0.0, 0.0);
+    assert!(falling < -5.0);
+    assert!(rising.abs() < 0.2, "bounced {rising}");
+}
@@ -0,0 +1,54 @@
+use bevy_flatland::scalar::*;
+use bevy_flatland::simulation::*;
+use bevy_flatland::simulation::convergence::*;
+
+//How far below its order a method's observed order may fall, which covers the leading error term not dominating yet.
+//Single precision leaves the sixth order methods a single halving before the error floor, well before that happens
+#[cfg(not(feature = "f64"))]
+const ORDER_TOLERANCE: Scalar = 0.4;
+#[cfg(feature = "f64")]
+const ORDER_TOLERANCE: Scalar = 0.15;
+
+fn check(result: &ConvergenceResult, expected: usize){
+    let observed = result.observed_order.unwrap_or_else(|| panic!(
+        "{} on the {} has no order estimate, errors {:?}", result.method, result.problem.name(), result.errors
+    ));
+    assert!(
+        observed >= expected as Scalar - ORDER_TOLERANCE,
+        "{} on the {} converges at order {observed}, expected {expected}, errors {:?}", result.method, result.problem.name(), result.errors
+    );
+}
+
+#[test]
+fn presets_converge_at_their_order(){
+    let results = convergence_table();
+    assert_eq!(results.len(), DiffEqSolverConfig::PRESETS.len()*ReferenceProblem::ALL.len());
+    for result in results.iter(){
+        check(result, DiffEqSolverConfig::preset(&result.method).unwrap().accuracy_order());
+    }
+}
+
+#[test]
+fn symplectic_methods_converge_at_their_order_on_conservative_problems(){
+    let methods = [
+        ("semi_implicit_euler", SymplecticSolverConfig::semi_implicit_euler()),
+        ("velocity_verlet", SymplecticSolverConfig::velocity_verlet()),
+        ("leapfrog", SymplecticSolverConfig::leapfrog()),
+        ("forest_ruth", SymplecticSolverConfig::forest_ruth())
+    ];
+    for (name, config) in methods.iter(){
+        for problem in ReferenceProblem::ALL.into_iter().filter(ReferenceProblem::is_conservative){
+            check(&measure_symplectic_convergence(name, config, problem, &convergence_step_counts(config.order())), config.order());
+        }
+    }
+}
+
+//Coefficients rounded to single precision would leave an error floor around 1e-10 over these step counts
+#[cfg(feature = "f64")]
+#[test]
+fn ralston_fourth_keeps_its_order_in_double_precision(){
+    for problem in ReferenceProblem::ALL{
+        let result = measure_convergence("ralston_fourth", &DiffEqSolverConfig::ralston_fourth(), problem, &[64, 128, 256, 512]);
+        check(&result, 4);
+    }
+}
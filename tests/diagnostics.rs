@@ -0,0 +1,95 @@
+use bevy::{prelude::*, diagnostic::DiagnosticsStore};
+use bevy_flatland::components::RigidBody2D;
+use bevy_flatland::scalar::*;
+use bevy_flatland::simulation::*;
+mod common;
+use common::*;
+
+fn attractor_energy(min_distance: Scalar, distance: Scalar) -> Scalar{
+    let body = RigidBody2D::new(2.0, 1.0);
+    let model = ForceModel2D{
+        bodies: vec![BodyForces2D{gravity_scale: 1.0, ..Default::default()}],
+        attractors: vec![(Vector::ZERO, PointAttractor2D{strength: 3.0, min_distance})],
+        ..Default::default()
+    };
+    model.potential_energy(&[RigidBodyState2D{position: Vector::new(distance, 0.0), ..Default::default()}], &[&body])
+}
+
+#[test]
+fn attractor_potential_stays_finite_at_its_center(){
+    for min_distance in [0.0, 0.5]{
+        assert!(attractor_energy(min_distance, 0.0).is_finite(), "min_distance = {min_distance}");
+    }
+    assert_eq!(attractor_energy(0.0, 2.0), -3.0);
+    //continuous where the force stops growing, and linear inside it
+    let (inside, outside) = (attractor_energy(0.5, 0.5 - 1e-4), attractor_energy(0.5, 0.5));
+    assert!((inside - outside).abs() < 1e-2, "{inside} {outside}");
+    assert!((attractor_energy(0.5, 0.25) - (-12.0 - 6.0)).abs() < 1e-4);
+}
+
+fn diagnostics(app: &App) -> SimulationDiagnostics{
+    app.world.resource::<SimulationDiagnostics>().clone()
+}
+
+fn published(app: &App, id: bevy::diagnostic::DiagnosticId) -> Scalar{
+    app.world.resource::<DiagnosticsStore>().get(id).and_then(|diagnostic| diagnostic.value()).unwrap() as Scalar
+}
+
+#[test]
+fn free_bodies_keep_their_energy_and_momentum(){
+    let mut app = world();
+    app.insert_resource(UniformGravity2D{acceleration: Vector::ZERO});
+    let body = spawn_body(&mut app, 1.0, 2.0);
+    app.world.entity_mut(body).insert(RigidBody2D::new(2.0, 0.5));
+    app.world.get_mut::<SimulationData>(body).unwrap().state = RigidBodyState2D{
+        position: Vector::new(1.0, 2.0), velocity: Vector::new(3.0, -1.0), angular_velocity: 4.0, ..Default::default()
+    };
+    step(&mut app);
+    let first = diagnostics(&app);
+    //m*v^2/2 + I*w^2/2, and m*(r x v) + I*w around the origin
+    assert!((first.current.kinetic_energy - (10.0 + 4.0)).abs() < 1e-4, "{first:?}");
+    assert_eq!(first.current.potential_energy, 0.0);
+    assert!((first.current.linear_momentum - Vector::new(6.0, -2.0)).length() < 1e-4, "{first:?}");
+    assert_eq!(first.baseline, Some(first.current));
+    for _ in 0..120{
+        step(&mut app);
+    }
+    let after = diagnostics(&app);
+    assert!(state(&app, body).position.x > 6.0);
+    assert!(after.energy_drift().abs() < 1e-3, "{after:?}");
+    assert!(after.linear_momentum_drift().length() < 1e-3, "{after:?}");
+    assert!(after.angular_momentum_drift().abs() < 1e-3, "{after:?}");
+    let expected_angular = Vector::new(1.0, 2.0).perp_dot(Vector::new(6.0, -2.0)) + 0.5*4.0;
+    assert!((after.current.angular_momentum - expected_angular).abs() < 1e-3, "{after:?}");
+    assert!((published(&app, SimulationDiagnostics::KINETIC_ENERGY) - 14.0).abs() < 1e-3);
+    assert!((published(&app, SimulationDiagnostics::LINEAR_MOMENTUM) - (40.0 as Scalar).sqrt()).abs() < 1e-3);
+    assert!(published(&app, SimulationDiagnostics::ENERGY_DRIFT).abs() < 1e-3);
+}
+
+#[test]
+fn springs_trade_kinetic_for_potential_energy_without_drift(){
+    let mut app = world();
+    app.insert_resource(UniformGravity2D{acceleration: Vector::ZERO});
+    let a = spawn_body(&mut app, 0.0, 0.0);
+    let b = spawn_body(&mut app, 3.0, 0.0);
+    //stretched by 1 and sent spinning around each other
+    app.world.spawn(DampedSpring2D{body_a: a, local_anchor_a: Vector::ZERO, body_b: Some(b), anchor_b: Vector::ZERO, rest_length: 2.0, stiffness: 20.0, damping: 0.0});
+    app.world.get_mut::<SimulationData>(a).unwrap().state.velocity = Vector::new(0.0, -1.0);
+    app.world.get_mut::<SimulationData>(b).unwrap().state.velocity = Vector::new(0.5, 1.5);
+    step(&mut app);
+    let baseline = diagnostics(&app).baseline.unwrap();
+    assert!(baseline.potential_energy > 0.0 && baseline.kinetic_energy > 0.0, "{baseline:?}");
+    let mut exchanged: Scalar = 0.0;
+    for _ in 0..600{
+        step(&mut app);
+        let diagnostics = diagnostics(&app);
+        exchanged = exchanged.max((diagnostics.current.potential_energy - baseline.potential_energy).abs());
+        assert!(diagnostics.relative_energy_drift().abs() < 1e-3, "{diagnostics:?}");
+        assert!(diagnostics.linear_momentum_drift().length() < 1e-3, "{diagnostics:?}");
+        assert!(diagnostics.angular_momentum_drift().abs() < 1e-3, "{diagnostics:?}");
+    }
+    assert!(exchanged > 1.0, "{exchanged}");
+    let diagnostics = diagnostics(&app);
+    assert!((published(&app, SimulationDiagnostics::TOTAL_ENERGY) - diagnostics.current.total_energy()).abs() < 1e-4);
+    assert!((published(&app, SimulationDiagnostics::POTENTIAL_ENERGY) - diagnostics.current.potential_energy).abs() < 1e-4);
+}
@@ -0,0 +1,76 @@
+use bevy::prelude::*;
+use bevy_flatland::components::*;
+use bevy_flatland::scalar::*;
+use bevy_flatland::simulation::*;
+mod common;
+use common::*;
+
+const MASS: Scalar = 2.0;
+const INERTIA: Scalar = 0.5;
+
+//A body off the origin in a world without gravity
+fn floating_body() -> (App, Entity){
+    let mut app = world();
+    app.insert_resource(UniformGravity2D{acceleration: Vector::ZERO});
+    let body = spawn_body(&mut app, 1.0, 2.0);
+    app.world.entity_mut(body).insert(RigidBody2D::new(MASS, INERTIA));
+    (app, body)
+}
+
+fn data(app: &mut App, entity: Entity) -> Mut<'_, SimulationData>{
+    app.world.get_mut::<SimulationData>(entity).unwrap()
+}
+
+#[test]
+fn forces_off_the_centre_of_mass_add_their_torque(){
+    let mut data = SimulationData{state: RigidBodyState2D{position: Vector::new(1.0, 2.0), ..Default::default()}, ..Default::default()};
+    //r = (2, 0) and F = (0, 3), so r x F = 6
+    data.apply_force_at_point(Vector::new(0.0, 3.0), Vector::new(3.0, 2.0));
+    assert_eq!((data.force, data.torque), (Vector::new(0.0, 3.0), 6.0));
+    //r = (0, -1) and F = (2, 0), so r x F = 2
+    data.apply_force_at_point(Vector::new(2.0, 0.0), Vector::new(1.0, 1.0));
+    data.apply_torque(-1.5);
+    assert_eq!((data.force, data.torque), (Vector::new(2.0, 3.0), 6.5));
+    //through the centre of mass there is no torque
+    data.apply_force_at_point(Vector::new(-2.0, -3.0), Vector::new(1.0, 2.0) + Vector::new(-2.0, -3.0));
+    assert_eq!((data.force, data.torque), (Vector::ZERO, 6.5));
+}
+
+#[test]
+fn impulses_change_velocity_once(){
+    let (mut app, body) = floating_body();
+    let impulse = Vector::new(0.0, 4.0);
+    let arm = Vector::new(1.0, -0.5);
+    let point = state(&app, body).position + arm;
+    data(&mut app, body).apply_impulse_at_point(impulse, point);
+    data(&mut app, body).apply_angular_impulse(0.25);
+    step(&mut app);
+    let expected_spin = (arm.perp_dot(impulse) + 0.25)/INERTIA;
+    let after = state(&app, body);
+    assert!((after.velocity - impulse/MASS).length() < 1e-5, "{after:?}");
+    assert!((after.angular_velocity - expected_spin).abs() < 1e-5, "{after:?}");
+    let data = app.world.get::<SimulationData>(body).unwrap();
+    assert_eq!((data.linear_impulse, data.angular_impulse), (Vector::ZERO, 0.0));
+    //consumed by the first step, so the next one leaves the velocity alone
+    step(&mut app);
+    assert!((state(&app, body).velocity - after.velocity).length() < 1e-5);
+    assert!((state(&app, body).angular_velocity - after.angular_velocity).abs() < 1e-5);
+}
+
+#[test]
+fn forces_act_over_a_single_step(){
+    let (mut app, body) = floating_body();
+    let dt = app.world.resource::<FlatlandTimestep>().step;
+    let force = Vector::new(6.0, 0.0);
+    let arm = Vector::new(0.0, 1.0);
+    let point = state(&app, body).position + arm;
+    data(&mut app, body).apply_force_at_point(force, point);
+    step(&mut app);
+    let after = state(&app, body);
+    assert!((after.velocity - force/MASS*dt).length() < 1e-5, "{after:?}");
+    assert!((after.angular_velocity - arm.perp_dot(force)/INERTIA*dt).abs() < 1e-5, "{after:?}");
+    let data = app.world.get::<SimulationData>(body).unwrap();
+    assert_eq!((data.force, data.torque), (Vector::ZERO, 0.0));
+    step(&mut app);
+    assert!((state(&app, body).velocity - after.velocity).length() < 1e-5);
+}
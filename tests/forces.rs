@@ -0,0 +1,143 @@
+use bevy::{prelude::*, ecs::system::SystemState};
+use bevy_flatland::components::*;
+use bevy_flatland::scalar::*;
+use bevy_flatland::simulation::*;
+mod common;
+use common::*;
+
+const TOLERANCE: Scalar = 1e-4;
+
+//Net force and torque on every body, recovered from the accelerations the model gives them
+fn net_forces(model: &ForceModel2D, states: &[RigidBodyState2D], bodies: &[&RigidBody2D]) -> Vec<(Vector, Scalar)>{
+    let mut derivatives = vec![RigidBodyState2D::default(); states.len()];
+    model.derivatives(states, bodies, &mut derivatives);
+    derivatives.iter().zip(bodies).map(|(derivative, body)| (derivative.velocity*body.mass, derivative.angular_velocity*body.rotational_inertia)).collect()
+}
+
+fn moving(position: Vector, velocity: Vector) -> RigidBodyState2D{
+    RigidBodyState2D{position, velocity, ..Default::default()}
+}
+
+fn assert_force((force, torque): (Vector, Scalar), expected_force: Vector, expected_torque: Scalar){
+    assert!((force - expected_force).length() < TOLERANCE, "force {force}, expected {expected_force}");
+    assert!((torque - expected_torque).abs() < TOLERANCE, "torque {torque}, expected {expected_torque}");
+}
+
+#[test]
+fn springs_pull_by_their_stretch_and_damp_their_closing_speed(){
+    let body = RigidBody2D::new(2.0, 1.0);
+    let spring = |a, b, damping| ForceModel2D{
+        springs: vec![SpringForce2D{a, b, rest_length: 1.0, stiffness: 10.0, damping}],
+        ..Default::default()
+    };
+    //stretched by 2 and moving away from the anchor at 1, so k*2 + c*1 pulls it back
+    let to_world = spring(SpringAnchor2D::Body{index: 0, local_anchor: Vector::ZERO}, SpringAnchor2D::World(Vector::ZERO), 2.0);
+    let forces = net_forces(&to_world, &[moving(Vector::new(3.0, 0.0), Vector::new(1.0, 0.0))], &[&body]);
+    assert_force(forces[0], Vector::new(-22.0, 0.0), 0.0);
+    //moving sideways doesn't change the length, so it isn't damped
+    let forces = net_forces(&to_world, &[moving(Vector::new(3.0, 0.0), Vector::new(0.0, 1.0))], &[&body]);
+    assert_force(forces[0], Vector::new(-20.0, 0.0), 0.0);
+
+    //between two bodies, with the anchor on a a unit above its centre
+    let between = spring(SpringAnchor2D::Body{index: 0, local_anchor: Vector::Y}, SpringAnchor2D::Body{index: 1, local_anchor: Vector::ZERO}, 0.0);
+    let forces = net_forces(&between, &[moving(Vector::ZERO, Vector::ZERO), moving(Vector::new(3.0, 1.0), Vector::ZERO)], &[&body, &body]);
+    assert_force(forces[0], Vector::new(20.0, 0.0), Vector::Y.perp_dot(Vector::new(20.0, 0.0)));
+    assert_force(forces[1], Vector::new(-20.0, 0.0), 0.0);
+}
+
+#[test]
+fn quadratic_drag_opposes_velocity_with_its_square(){
+    let body = RigidBody2D::new(2.0, 1.0);
+    let model = ForceModel2D{
+        bodies: vec![BodyForces2D{quadratic_drag: QuadraticDrag2D{linear: 0.5, angular: 0.25}, ..Default::default()}],
+        ..Default::default()
+    };
+    let state = RigidBodyState2D{velocity: Vector::new(3.0, 4.0), angular_velocity: -2.0, ..Default::default()};
+    let (force, torque) = net_forces(&model, &[state], &[&body])[0];
+    //|F| = k*|v|^2 = 0.5*25
+    assert!((force.length() - 12.5).abs() < TOLERANCE, "{force}");
+    assert!((force.normalize() + state.velocity.normalize()).length() < TOLERANCE, "{force}");
+    assert!((torque - 1.0).abs() < TOLERANCE, "{torque}");
+}
+
+#[test]
+fn attractors_pull_by_the_inverse_square_up_to_min_distance(){
+    let body = RigidBody2D::new(2.0, 1.0);
+    let model = ForceModel2D{
+        bodies: vec![BodyForces2D::default()],
+        attractors: vec![(Vector::new(1.0, 0.0), PointAttractor2D{strength: 3.0, min_distance: 1.0})],
+        ..Default::default()
+    };
+    //G*m/r^2 towards the attractor
+    let forces = net_forces(&model, &[moving(Vector::new(1.0, 2.0), Vector::ZERO)], &[&body]);
+    assert_force(forces[0], Vector::new(0.0, -1.5), 0.0);
+    //inside min_distance the pull is that at min_distance
+    let forces = net_forces(&model, &[moving(Vector::new(1.5, 0.0), Vector::ZERO)], &[&body]);
+    assert_force(forces[0], Vector::new(-6.0, 0.0), 0.0);
+    let forces = net_forces(&model, &[moving(Vector::new(1.0, 0.0), Vector::ZERO)], &[&body]);
+    assert_force(forces[0], Vector::ZERO, 0.0);
+}
+
+#[test]
+fn models_gather_every_generator_in_the_world(){
+    let mut app = world();
+    let body = spawn_body(&mut app, 0.0, 0.0);
+    app.world.entity_mut(body).insert((GravityScale2D(0.5), QuadraticDrag2D{linear: 0.1, angular: 0.2}));
+    let other = spawn_body(&mut app, 5.0, 0.0);
+    //turned a quarter turn, so its local anchor (1, 0) is at (2, 3)
+    let post_transform = Transform::from_xyz(2.0, 2.0, 0.0).with_rotation(Quat::from_rotation_z(std::f32::consts::FRAC_PI_2));
+    let post = app.world.spawn((post_transform, GlobalTransform::from(post_transform))).id();
+    app.world.spawn(PointAttractor2D{strength: 1.0, min_distance: 0.1}).insert(GlobalTransform::from_xyz(-4.0, 1.0, 0.0));
+    let spring = |body_a, body_b, anchor_b| DampedSpring2D{
+        body_a, local_anchor_a: Vector::ZERO, body_b, anchor_b, rest_length: 1.0, stiffness: 2.0, damping: 0.5
+    };
+    app.world.spawn(spring(body, Some(other), Vector::new(0.5, 0.0)));
+    app.world.spawn(spring(body, Some(post), Vector::new(1.0, 0.0)));
+    app.world.spawn(spring(other, None, Vector::new(7.0, 7.0)));
+
+    let mut generators: SystemState<ForceGenerators> = SystemState::new(&mut app.world);
+    let model = generators.get(&app.world).model(&[body, other]);
+    assert_eq!(model.gravity, Vector::new(0.0, -9.81));
+    assert_eq!(model.bodies[0].gravity_scale, 0.5);
+    assert_eq!(model.bodies[0].quadratic_drag.angular, 0.2);
+    assert_eq!(model.bodies[1].gravity_scale, 1.0);
+    assert_eq!(model.attractors.len(), 1);
+    assert_eq!(model.attractors[0].0, Vector::new(-4.0, 1.0));
+    assert_eq!(model.springs.len(), 3);
+    let mut world_ends = model.springs.iter().filter_map(|spring| match spring.b {
+        SpringAnchor2D::World(point) => Some(point),
+        SpringAnchor2D::Body{..} => None
+    }).collect::<Vec<_>>();
+    world_ends.sort_unstable_by(|a, b| a.x.total_cmp(&b.x));
+    //the post isn't simulated, so its end of the spring is fixed where its transform puts it
+    assert_eq!(world_ends.len(), 2, "{:?}", model.springs);
+    assert!((world_ends[0] - Vector::new(2.0, 3.0)).length() < TOLERANCE, "{:?}", model.springs);
+    assert_eq!(world_ends[1], Vector::new(7.0, 7.0));
+    assert!(model.springs.iter().any(|spring| matches!(spring.b, SpringAnchor2D::Body{index: 1, ..})), "{:?}", model.springs);
+    assert_eq!(model.couplings().collect::<Vec<_>>(), vec![(0, 1)]);
+}
+
+#[test]
+fn forces_are_evaluated_at_every_stage(){
+    let mut app = world();
+    app.insert_resource(UniformGravity2D{acceleration: Vector::ZERO});
+    let body = spawn_body(&mut app, 1.0, 0.0);
+    //a period of exactly a second on a unit mass
+    let stiffness = 4.0*PI*PI;
+    app.world.spawn(DampedSpring2D{
+        body_a: body, local_anchor_a: Vector::ZERO, body_b: None, anchor_b: Vector::ZERO, rest_length: 0.0, stiffness, damping: 0.0
+    });
+    let steps_per_period = (1.0/app.world.resource::<FlatlandTimestep>().step).round() as usize;
+    assert_eq!(steps_per_period, 60);
+    for _ in 0..steps_per_period/2{
+        step(&mut app);
+    }
+    assert!((state(&app, body).position - Vector::new(-1.0, 0.0)).length() < 1e-4, "{:?}", state(&app, body));
+    for _ in 0..steps_per_period/2{
+        step(&mut app);
+    }
+    //back where it started, which forces frozen for a whole step would overshoot by almost a fifth
+    let after = state(&app, body);
+    assert!((after.position - Vector::new(1.0, 0.0)).length() < 1e-4, "{after:?}");
+    assert!(after.velocity.length() < 1e-3, "{after:?}");
+}
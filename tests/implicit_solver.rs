@@ -0,0 +1,74 @@
+use bevy_flatland::components::RigidBody2D;
+use bevy_flatland::scalar::*;
+use bevy_flatland::simulation::*;
+
+fn stiff_chain(count: usize, coupled: bool) -> ForceModel2D{
+    let mut springs: Vec<SpringForce2D> = (0..count).map(|index| SpringForce2D{
+        a: SpringAnchor2D::Body{index, local_anchor: Vector::ZERO},
+        b: SpringAnchor2D::World(Vector::new(index as Scalar, 0.0)),
+        rest_length: 0.0,
+        stiffness: 1e4,
+        damping: 0.0
+    }).collect();
+    if coupled {
+        springs.extend((1..count).map(|index| SpringForce2D{
+            a: SpringAnchor2D::Body{index: index - 1, local_anchor: Vector::ZERO},
+            b: SpringAnchor2D::Body{index, local_anchor: Vector::ZERO},
+            rest_length: 1.0,
+            stiffness: 1e2,
+            damping: 0.0
+        }));
+    }
+    ForceModel2D{
+        bodies: vec![BodyForces2D{gravity_scale: 1.0, ..Default::default()}; count],
+        springs,
+        ..Default::default()
+    }
+}
+
+fn displaced(count: usize) -> Vec<RigidBodyState2D>{
+    (0..count).map(|index| RigidBodyState2D{position: Vector::new(index as Scalar, 0.1), ..Default::default()}).collect()
+}
+
+#[test]
+fn newton_jacobian_evaluations_dont_grow_with_uncoupled_bodies(){
+    let body = RigidBody2D::new(1.0, 1.0);
+    let bodies = vec![&body; 64];
+    let model = stiff_chain(64, false);
+    let config = DiffEqSolverConfig::gauss_legendre4();
+    let mut states = displaced(64);
+    let mut workspace = SolverWorkspace::default();
+    workspace.set_couplings(model.couplings());
+    let mut evaluations = 0;
+    let converged = config.step(0.0, 1.0/60.0, &mut states, &mut workspace, |_, states: &[RigidBodyState2D], derivatives: &mut [RigidBodyState2D]| {
+        evaluations += 1;
+        model.derivatives(states, &bodies, derivatives);
+    });
+    assert!(converged);
+    //one evaluation per state component for the Jacobians, the rest are iterations
+    assert!(evaluations < 64, "{evaluations} derivative evaluations for 64 bodies");
+}
+
+#[test]
+fn newton_converges_on_coupled_stiff_springs(){
+    let body = RigidBody2D::new(1.0, 1.0);
+    let bodies = vec![&body; 8];
+    let model = stiff_chain(8, true);
+    //forces of 1e3 leave single precision residuals well above the default tolerance
+    let tolerance = 0.1*Scalar::EPSILON.sqrt();
+    for config in [DiffEqSolverConfig::backward_euler(), DiffEqSolverConfig::gauss_legendre4(), DiffEqSolverConfig::radau_iia5()]{
+        let config = config.with_stage_solver(StageSolver::Newton{max_iterations: 50, tolerance});
+        let mut states = displaced(8);
+        let mut workspace = SolverWorkspace::default();
+        workspace.set_couplings(model.couplings());
+        for step in 0..120{
+            let converged = config.step(step as Scalar/60.0, 1.0/60.0, &mut states, &mut workspace, |_, states: &[RigidBodyState2D], derivatives: &mut [RigidBodyState2D]| {
+                model.derivatives(states, &bodies, derivatives);
+            });
+            assert!(converged, "stage equations didn't converge at step {step}");
+        }
+        for (index, state) in states.iter().enumerate(){
+            assert!((state.position - Vector::new(index as Scalar, 0.0)).length() <= 0.1 + 1e-3, "body {index} gained energy: {:?}", state.position);
+        }
+    }
+}
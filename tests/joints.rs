@@ -0,0 +1,73 @@
+use bevy::prelude::*;
+use bevy_flatland::collision::*;
+use bevy_flatland::components::*;
+use bevy_flatland::scalar::*;
+use bevy_flatland::simulation::*;
+mod common;
+use common::*;
+
+//World position of a point fixed to a body, whose centre of mass is its transform origin
+fn world_point(app: &App, entity: Entity, local: Vector) -> Vector{
+    let state = state(app, entity);
+    state.position + Vector::from_angle(state.angle).rotate(local)
+}
+
+//Steps the world, returning the largest of <error> over the first and the last ten steps
+fn error_before_and_after(app: &mut App, steps: usize, error: impl Fn(&App) -> Scalar) -> (Scalar, Scalar){
+    let mut errors = vec![error(app)];
+    for _ in 0..steps{
+        step(app);
+        errors.push(error(app));
+    }
+    let largest = |errors: &[Scalar]| errors.iter().copied().fold(0.0, Scalar::max);
+    (largest(&errors[..10]), largest(&errors[errors.len() - 10..]))
+}
+
+#[test]
+fn revolute_joints_pull_bodies_onto_world_anchors(){
+    let mut app = world();
+    let pendulum = spawn_body(&mut app, 2.0, 5.0);
+    //the pivot is a little off the end of the pendulum, which swings under gravity once it has been pulled there
+    let pivot = Vector::new(-0.3, 5.2);
+    let local_anchor = Vector::new(-2.0, 0.0);
+    app.world.spawn(Joint2D::new(JointKind2D::Revolute{limits: None, motor: None}, pendulum, local_anchor, None, pivot));
+    let (before, after) = error_before_and_after(&mut app, 120, |app| world_point(app, pendulum, local_anchor).distance(pivot));
+    assert!(before > 0.3, "{before}");
+    assert!(after < 0.01, "{after}");
+    assert!(world_point(&app, pendulum, Vector::ZERO).y < 5.0);
+}
+
+#[test]
+fn joints_hold_bodies_to_entities_without_simulation_data(){
+    let mut app = world();
+    //a static collider turned a quarter turn, so its local anchor (1, 0) is at (10, 6) in world space
+    let transform = Transform::from_xyz(10.0, 5.0, 0.0).with_rotation(Quat::from_rotation_z(std::f32::consts::FRAC_PI_2));
+    let wall = app.world.spawn((
+        transform, GlobalTransform::from(transform),
+        BoxCollider2D{center_position: Vector::ZERO, half_extents: Vector::splat(0.5), restitution: 0.0, friction: 0.6}
+    )).id();
+    let pivot = Vector::new(10.0, 6.0);
+    let hanging = spawn_body(&mut app, 10.0, 3.5);
+    let local_anchor = Vector::new(0.0, 2.0);
+    app.world.spawn(Joint2D::new(JointKind2D::Revolute{limits: None, motor: None}, hanging, local_anchor, Some(wall), Vector::new(1.0, 0.0)));
+    let (before, after) = error_before_and_after(&mut app, 120, |app| world_point(app, hanging, local_anchor).distance(pivot));
+    assert!(before > 0.4, "{before}");
+    assert!(after < 0.01, "{after}");
+    //hangs still below the pivot rather than falling
+    assert!((world_point(&app, hanging, Vector::ZERO) - Vector::new(10.0, 4.0)).length() < 0.02);
+}
+
+#[test]
+fn distance_joints_converge_between_two_bodies(){
+    let mut app = world();
+    let a = spawn_body(&mut app, 0.0, 0.0);
+    let b = spawn_body(&mut app, 3.0, 1.0);
+    app.world.entity_mut(b).get_mut::<SimulationData>().unwrap().state.angular_velocity = 2.0;
+    let length = 2.0;
+    app.world.spawn(Joint2D::new(JointKind2D::Distance{length}, a, Vector::new(0.5, 0.0), Some(b), Vector::new(0.0, 0.5)));
+    let (before, after) = error_before_and_after(&mut app, 120, |app| {
+        (world_point(app, a, Vector::new(0.5, 0.0)).distance(world_point(app, b, Vector::new(0.0, 0.5))) - length).abs()
+    });
+    assert!(before > 0.5, "{before}");
+    assert!(after < 0.01, "{after}");
+}
@@ -0,0 +1,55 @@
+use bevy::prelude::*;
+use bevy_flatland::collision::*;
+use bevy_flatland::components::*;
+use bevy_flatland::scalar::*;
+use bevy_flatland::simulation::*;
+mod common;
+use common::*;
+
+#[test]
+fn kinematic_platforms_carry_the_bodies_on_them(){
+    let mut app = world();
+    let platform_transform = Transform::default();
+    let platform = app.world.spawn((
+        platform_transform, GlobalTransform::from(platform_transform), SimulationData::from_transform(&platform_transform),
+        RigidBody2D::new(1.0, 1.0).with_body_type(RigidBodyType2D::KinematicPositionBased),
+        BoxCollider2D{center_position: Vector::ZERO, half_extents: Vector::new(2.0, 0.25), restitution: 0.0, friction: 0.8}
+    )).id();
+    let carried = spawn_box(&mut app, 0.0, 0.75, unit_box(0.0, 0.8));
+    //a static wall the platform passes straight through
+    let wall = app.world.spawn((
+        Transform::from_xyz(1.0, 0.0, 0.0), GlobalTransform::from_xyz(1.0, 0.0, 0.0),
+        BoxCollider2D{center_position: Vector::ZERO, half_extents: Vector::new(0.1, 0.2), restitution: 0.0, friction: 0.8}
+    )).id();
+    //ramps up to a unit per second to the right, so friction can keep up
+    let speed = |time: Scalar| time.min(1.0);
+    let (mut time, mut travelled) = (0.0, 0.0);
+    for _ in 0..180{
+        let dt = app.world.resource::<FlatlandTimestep>().step;
+        time += dt;
+        travelled += speed(time)*dt;
+        app.world.get_mut::<SimulationData>(platform).unwrap().set_kinematic_target(Vector::new(travelled, 0.0), 0.0);
+        step(&mut app);
+        let contacts = &app.world.resource::<Contacts2D>().contacts;
+        assert!(!contacts.iter().any(|contact| [contact.collider_a, contact.collider_b].contains(&wall)));
+    }
+    let (platform_state, carried_state) = (state(&app, platform), state(&app, carried));
+    assert!((platform_state.position.x - travelled).abs() < 1e-4, "{platform_state:?}");
+    //still riding the platform where it was put, at its speed
+    assert!((carried_state.position - Vector::new(travelled, 0.75)).length() < 0.05, "{carried_state:?}, travelled {travelled}");
+    assert!((carried_state.velocity.x - 1.0).abs() < 0.05, "{carried_state:?}");
+    assert!(app.world.get::<Sleeping>(carried).is_none());
+}
+
+#[test]
+fn kinematic_targets_turn_the_shorter_way_round(){
+    let body = RigidBody2D::new(1.0, 1.0).with_body_type(RigidBodyType2D::KinematicPositionBased);
+    let dt = 0.1;
+    for (from, to, turn) in [(3.1, -3.1, 2.0*PI - 6.2), (-3.1, 3.1, 6.2 - 2.0*PI), (0.5, 1.0, 0.5), (0.0, 7.0, 7.0 - 2.0*PI), (0.0, PI, PI), (0.0, -PI, PI)]{
+        let mut data = SimulationData::default();
+        data.state.angle = from;
+        data.set_kinematic_target(Vector::ZERO, to);
+        data.drive(&body, dt);
+        assert!((data.state.angular_velocity - turn/dt).abs() < 1e-3, "from {from} to {to}: {}", data.state.angular_velocity);
+    }
+}
@@ -0,0 +1,88 @@
+use bevy::{prelude::*, ecs::event::ManualEventReader};
+use bevy_flatland::collision::*;
+use bevy_flatland::components::*;
+use bevy_flatland::scalar::*;
+mod common;
+use common::*;
+
+//Ground in group 1, ending at x = 10
+fn spawn_layered_ground(app: &mut App) -> Entity{
+    let ground = spawn_ground(app, 10.0, 0.0, 0.6);
+    app.world.entity_mut(ground).insert(CollisionLayers2D::new(1, u32::MAX));
+    ground
+}
+
+fn spawn_reporting_box(app: &mut App, x: f32, y: f32) -> Entity{
+    let body = spawn_box(app, x, y, unit_box(0.0, 0.6));
+    app.world.entity_mut(body).insert(CollidingEntities::default());
+    body
+}
+
+#[test]
+fn layers_decide_which_colliders_collide(){
+    let mut app = world();
+    spawn_layered_ground(&mut app);
+    let landing = spawn_reporting_box(&mut app, 0.0, 3.0);
+    //in group 2 and filtering out group 1, so it falls through the ground
+    let ghost = spawn_reporting_box(&mut app, 3.0, 3.0);
+    app.world.entity_mut(ghost).insert(CollisionLayers2D::new(2, !1));
+    //only filters for group 3, which the ground isn't in
+    let loner = spawn_reporting_box(&mut app, 6.0, 3.0);
+    app.world.entity_mut(loner).insert(CollisionLayers2D::new(u32::MAX, 4));
+    for _ in 0..120{
+        step(&mut app);
+    }
+    assert!((state(&app, landing).position.y - 1.0).abs() < 0.02, "{:?}", state(&app, landing).position);
+    assert!(state(&app, ghost).position.y < -10.0, "{:?}", state(&app, ghost).position);
+    assert!(state(&app, loner).position.y < -10.0, "{:?}", state(&app, loner).position);
+    assert!(app.world.get::<CollidingEntities>(ghost).unwrap().0.is_empty());
+    //filtered pairs never reach the narrow phase
+    let broad_phase = app.world.resource::<BroadPhase2D>();
+    assert!(broad_phase.pairs().iter().all(|(a, b)| ![*a, *b].contains(&ghost) && ![*a, *b].contains(&loner)));
+}
+
+#[test]
+fn sensors_report_overlaps_without_pushing(){
+    let mut app = world();
+    spawn_layered_ground(&mut app);
+    let sensor = app.world.spawn((
+        Transform::from_xyz(0.0, 3.0, 0.0), GlobalTransform::from_xyz(0.0, 3.0, 0.0),
+        BoxCollider2D{center_position: Vector::ZERO, half_extents: Vector::new(5.0, 1.0), restitution: 0.0, friction: 0.6},
+        Sensor2D, CollidingEntities::default()
+    )).id();
+    let falling = spawn_reporting_box(&mut app, 0.0, 6.0);
+    //falls freely beside the ground and the sensor, for comparison
+    let free = spawn_reporting_box(&mut app, 20.0, 6.0);
+    let mut started = ManualEventReader::<CollisionStarted>::default();
+    let mut ended = ManualEventReader::<CollisionEnded>::default();
+    let (mut entered, mut left, mut was_inside) = (None, None, false);
+    for step_index in 0..100{
+        step(&mut app);
+        for event in started.iter(app.world.resource::<Events<CollisionStarted>>()).filter(|event| [event.a, event.b].contains(&sensor)){
+            assert!([event.a, event.b].contains(&falling));
+            entered = Some(step_index);
+        }
+        for event in ended.iter(app.world.resource::<Events<CollisionEnded>>()).filter(|event| [event.a, event.b].contains(&sensor)){
+            assert!([event.a, event.b].contains(&falling));
+            left = Some(step_index);
+        }
+        let (falling_state, free_state) = (state(&app, falling), state(&app, free));
+        if falling_state.position.y > 1.5 {
+            //nothing but gravity acts on the box while it passes through the sensor
+            assert!((falling_state.position.y - free_state.position.y).abs() < 1e-4, "step {step_index}: {falling_state:?} {free_state:?}");
+            assert!((falling_state.velocity - free_state.velocity).length() < 1e-4, "step {step_index}: {falling_state:?} {free_state:?}");
+        }
+        if (2.0..=4.0).contains(&falling_state.position.y) {
+            was_inside = true;
+            assert!(app.world.get::<CollidingEntities>(sensor).unwrap().0.contains(&falling));
+        }
+    }
+    assert!(was_inside);
+    let (entered, left) = (entered.expect("no collision started with the sensor"), left.expect("no collision ended with the sensor"));
+    assert!(entered < left);
+    assert!(app.world.get::<CollidingEntities>(sensor).unwrap().0.is_empty());
+    //the sensor's contacts stay out of the solver
+    let contacts = app.world.resource::<Contacts2D>();
+    assert!(contacts.contacts.iter().filter(|contact| [contact.collider_a, contact.collider_b].contains(&sensor)).all(|contact| contact.sensor));
+    assert!((state(&app, falling).position.y - 1.0).abs() < 0.02);
+}
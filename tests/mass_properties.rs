@@ -0,0 +1,124 @@
+use bevy::prelude::*;
+use bevy_flatland::components::*;
+use bevy_flatland::scalar::*;
+use bevy_flatland::simulation::*;
+
+const TOLERANCE: Scalar = 1e-4;
+
+//A world that only runs update_mass_properties
+fn mass_world() -> (World, Schedule){
+    let mut schedule = Schedule::default();
+    schedule.add_systems(update_mass_properties);
+    (World::new(), schedule)
+}
+
+//Body at the origin whose mass properties are derived from its colliders
+fn spawn_compound(world: &mut World) -> Entity{
+    world.spawn((Transform::default(), SimulationData::default(), RigidBody2D::new(1.0, 1.0), RotationalInertiaTensorList2D::default())).id()
+}
+
+fn spawn_part(world: &mut World, body: Entity, transform: Transform, collider: impl Bundle) -> Entity{
+    let part = world.spawn((transform, collider)).id();
+    world.entity_mut(body).push_children(&[part]);
+    part
+}
+
+fn assert_mass(world: &World, body: Entity, mass: Scalar, center: Vector, inertia: Scalar){
+    let rigid_body = world.get::<RigidBody2D>(body).unwrap();
+    assert!((rigid_body.mass - mass).abs() < TOLERANCE, "mass {}, expected {mass}", rigid_body.mass);
+    assert!((rigid_body.center_of_mass - center).length() < TOLERANCE, "centre {}, expected {center}", rigid_body.center_of_mass);
+    assert!((rigid_body.rotational_inertia - inertia).abs() < TOLERANCE, "inertia {}, expected {inertia}", rigid_body.rotational_inertia);
+}
+
+fn listed_inertia(world: &World, body: Entity, part: Entity) -> Scalar{
+    world.get::<RotationalInertiaTensorList2D>(body).unwrap().inertia[&part.index()]
+}
+
+#[test]
+fn primitive_colliders_get_their_closed_form_mass(){
+    let (mut world, mut schedule) = mass_world();
+    //2 by 1 box: m = 2, I = m*(w^2 + h^2)/12
+    let rectangle = spawn_compound(&mut world);
+    world.entity_mut(rectangle).insert(BoxCollider2D{center_position: Vector::ZERO, half_extents: Vector::new(1.0, 0.5), restitution: 0.0, friction: 0.0});
+    //disc of radius 1/2 at density 2: m = 2*pi/4, I = m*r^2/2
+    let disc = spawn_compound(&mut world);
+    world.entity_mut(disc).insert((CircleCollider2D{center_position: Vector::ZERO, radius: 0.5, restitution: 0.0, friction: 0.0}, ColliderDensity2D(2.0)));
+    //a 1 by 2 rectangle between two half discs of radius 1/2
+    let capsule = spawn_compound(&mut world);
+    world.entity_mut(capsule).insert(CapsuleCollider2D{center_position: Vector::ZERO, half_height: 1.0, radius: 0.5, restitution: 0.0, friction: 0.0});
+    schedule.run(&mut world);
+
+    assert_mass(&world, rectangle, 2.0, Vector::ZERO, 2.0*5.0/12.0);
+    assert_mass(&world, disc, PI/2.0, Vector::ZERO, PI/16.0);
+    //the caps form a disc whose halves sit h away from the centre, each centroid a further 4r/(3 pi) out
+    let (r, h) = (0.5, 1.0);
+    let caps = PI*r*r;
+    let capsule_inertia = 2.0*(1.0 + 4.0)/12.0 + caps*(r*r/2.0 + h*h) + 8.0*h*r*r*r/3.0;
+    assert_mass(&world, capsule, 2.0 + caps, Vector::ZERO, capsule_inertia);
+    //a single collider holds all of its body's inertia
+    assert!((listed_inertia(&world, disc, disc) - PI/16.0).abs() < TOLERANCE);
+}
+
+#[test]
+fn polygons_use_their_density_and_centroid(){
+    let (mut world, mut schedule) = mass_world();
+    let body = spawn_compound(&mut world);
+    let points = [Vector::ZERO, Vector::new(3.0, 0.0), Vector::new(0.0, 3.0)];
+    world.entity_mut(body).insert((ConvexPolygonCollider2D::from_points(&points, 0.0, 0.0).unwrap(), ColliderDensity2D(2.0)));
+    schedule.run(&mut world);
+    //right triangle with legs a and b: centroid at a third of each, I = m*(a^2 + b^2)/18
+    assert_mass(&world, body, 9.0, Vector::new(1.0, 1.0), 9.0*18.0/18.0);
+}
+
+#[test]
+fn compound_bodies_add_their_parts_about_the_common_centre(){
+    let (mut world, mut schedule) = mass_world();
+    let body = spawn_compound(&mut world);
+    //m = 1 and I = 1/6, at (-1, 0)
+    let left = spawn_part(&mut world, body, Transform::from_xyz(-1.0, 0.0, 0.0), BoxCollider2D{
+        center_position: Vector::ZERO, half_extents: Vector::splat(0.5), restitution: 0.0, friction: 0.0
+    });
+    //m = 2 and I = 5/6, its offset centre turned a quarter turn onto (2, 0)
+    let right_transform = Transform::from_xyz(2.0, -0.5, 0.0).with_rotation(Quat::from_rotation_z(std::f32::consts::FRAC_PI_2));
+    let right = spawn_part(&mut world, body, right_transform, BoxCollider2D{
+        center_position: Vector::new(0.5, 0.0), half_extents: Vector::new(1.0, 0.5), restitution: 0.0, friction: 0.0
+    });
+    schedule.run(&mut world);
+
+    //the centre sits at (1, 0), 2 from the left box and 1 from the right one
+    assert!((listed_inertia(&world, body, left) - (1.0/6.0 + 4.0)).abs() < TOLERANCE);
+    assert!((listed_inertia(&world, body, right) - (5.0/6.0 + 2.0)).abs() < TOLERANCE);
+    assert_mass(&world, body, 3.0, Vector::new(1.0, 0.0), 7.0);
+}
+
+#[test]
+fn moving_a_part_recomputes_the_body_and_shifts_its_state(){
+    let (mut world, mut schedule) = mass_world();
+    let body = spawn_compound(&mut world);
+    let moving = RigidBodyState2D{velocity: Vector::new(1.0, 0.0), angular_velocity: 2.0, ..Default::default()};
+    world.get_mut::<SimulationData>(body).unwrap().state = moving;
+    let unit = || BoxCollider2D{center_position: Vector::ZERO, half_extents: Vector::splat(0.5), restitution: 0.0, friction: 0.0};
+    spawn_part(&mut world, body, Transform::default(), unit());
+    let part = spawn_part(&mut world, body, Transform::from_xyz(2.0, 0.0, 0.0), unit());
+    schedule.run(&mut world);
+    assert_mass(&world, body, 2.0, Vector::new(1.0, 0.0), 2.0/6.0 + 2.0);
+    //the state now follows the centre of mass, which the spin moves along with the body
+    let state = world.get::<SimulationData>(body).unwrap().state;
+    assert!((state.position - Vector::new(1.0, 0.0)).length() < TOLERANCE, "{state:?}");
+    assert!((state.velocity - Vector::new(1.0, 2.0)).length() < TOLERANCE, "{state:?}");
+
+    //unchanged colliders leave the body alone
+    world.get_mut::<RigidBody2D>(body).unwrap().mass = 5.0;
+    schedule.run(&mut world);
+    assert_eq!(world.get::<RigidBody2D>(body).unwrap().mass, 5.0);
+
+    //moving the part up by 2 moves the centre up by 1
+    world.get_mut::<Transform>(part).unwrap().translation = Vec3::new(2.0, 2.0, 0.0);
+    schedule.run(&mut world);
+    assert_mass(&world, body, 2.0, Vector::new(1.0, 1.0), 2.0/6.0 + 4.0);
+    assert!((listed_inertia(&world, body, part) - (1.0/6.0 + 2.0)).abs() < TOLERANCE);
+    let shifted = world.get::<SimulationData>(body).unwrap().state;
+    assert!((shifted.position - Vector::new(1.0, 1.0)).length() < TOLERANCE, "{shifted:?}");
+    assert!((shifted.velocity - Vector::new(-1.0, 2.0)).length() < TOLERANCE, "{shifted:?}");
+    assert_eq!(shifted.angular_velocity, 2.0);
+}
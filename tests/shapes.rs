@@ -0,0 +1,144 @@
+use bevy_flatland::collision::*;
+use bevy_flatland::components::*;
+use bevy_flatland::scalar::*;
+
+const TOLERANCE: Scalar = 1e-4;
+
+fn ground() -> BoxCollider2D{
+    BoxCollider2D{center_position: Vector::ZERO, half_extents: Vector::new(5.0, 0.5), restitution: 0.0, friction: 0.0}
+}
+
+fn at(x: Scalar, y: Scalar, angle: Scalar) -> Isometry2D{
+    Isometry2D::new(Vector::new(x, y), angle)
+}
+
+fn polygon(points: &[(Scalar, Scalar)]) -> ConvexPolygonCollider2D{
+    let points: Vec<Vector> = points.iter().map(|(x, y)| Vector::new(*x, *y)).collect();
+    ConvexPolygonCollider2D::from_points(&points, 0.0, 0.0).unwrap()
+}
+
+//Square house with a pointed roof, standing on its flat base
+fn house() -> ConvexPolygonCollider2D{
+    polygon(&[(-0.5, 0.0), (0.5, 0.0), (0.5, 1.0), (0.0, 1.5), (-0.5, 1.0)])
+}
+
+//The single manifold between two colliders, with its points sorted along x
+fn manifold(a: Collider2D, pose_a: Isometry2D, b: Collider2D, pose_b: Isometry2D) -> ContactManifold2D{
+    let mut manifolds = collide_colliders(a, pose_a, b, pose_b);
+    assert_eq!(manifolds.len(), 1, "{manifolds:?}");
+    let (_, mut manifold) = manifolds.remove(0);
+    manifold.points.sort_unstable_by(|a, b| a.point.x.total_cmp(&b.point.x));
+    manifold
+}
+
+fn assert_points(manifold: &ContactManifold2D, normal: Vector, expected: &[(Scalar, Scalar, Scalar)]){
+    assert!((manifold.normal - normal).length() < TOLERANCE, "{manifold:?}");
+    assert_eq!(manifold.points.len(), expected.len(), "{manifold:?}");
+    for (point, (x, y, depth)) in manifold.points.iter().zip(expected){
+        assert!((point.point - Vector::new(*x, *y)).length() < TOLERANCE && (point.depth - depth).abs() < TOLERANCE, "{point:?}, expected ({x}, {y}) {depth} deep");
+    }
+}
+
+#[test]
+fn hulls_drop_interior_and_collinear_points(){
+    let hull = polygon(&[(0.0, 0.0), (1.0, 0.0), (0.5, 0.2), (1.0, 1.0), (0.0, 1.0), (0.5, 0.5), (0.5, 0.0)]);
+    assert_eq!(hull.vertices, vec![Vector::new(0.0, 0.0), Vector::new(1.0, 0.0), Vector::new(1.0, 1.0), Vector::new(0.0, 1.0)]);
+    //counter-clockwise
+    let area: Scalar = (0..hull.vertices.len()).map(|index| hull.vertices[index].perp_dot(hull.vertices[(index + 1) % hull.vertices.len()])).sum();
+    assert!(area > 0.0);
+    let line = [Vector::ZERO, Vector::X, Vector::new(2.0, 0.0)];
+    assert!(ConvexPolygonCollider2D::from_points(&line, 0.0, 0.0).is_none());
+}
+
+#[test]
+fn polygon_resting_on_a_box_gets_its_base_clipped(){
+    let house = house();
+    let resting = manifold(Collider2D::Box(&ground()), at(0.0, 0.0, 0.0), Collider2D::ConvexPolygon(&house), at(1.0, 0.45, 0.0));
+    assert_points(&resting, Vector::Y, &[(0.5, 0.475, 0.05), (1.5, 0.475, 0.05)]);
+    //hanging over the end of the ground, the base is cut back to the ground's face
+    let overhanging = manifold(Collider2D::Box(&ground()), at(0.0, 0.0, 0.0), Collider2D::ConvexPolygon(&house), at(4.8, 0.45, 0.0));
+    assert_points(&overhanging, Vector::Y, &[(4.3, 0.475, 0.05), (5.0, 0.475, 0.05)]);
+}
+
+#[test]
+fn polygon_corner_touches_a_polygon_face(){
+    let floor = polygon(&[(-2.0, -0.5), (2.0, -0.5), (2.0, 0.5), (-2.0, 0.5)]);
+    //upside down, standing on the tip of its roof
+    let touching = manifold(Collider2D::ConvexPolygon(&floor), at(0.0, 0.0, 0.0), Collider2D::ConvexPolygon(&house()), at(0.0, 1.9, PI));
+    assert_points(&touching, Vector::Y, &[(0.0, 0.45, 0.1)]);
+    assert!(collide_colliders(Collider2D::ConvexPolygon(&floor), at(0.0, 0.0, 0.0), Collider2D::ConvexPolygon(&house()), at(0.0, 2.01, PI)).is_empty());
+}
+
+#[test]
+fn rounded_shapes_touch_at_their_surfaces(){
+    let circle = CircleCollider2D{center_position: Vector::ZERO, radius: 0.5, restitution: 0.0, friction: 0.0};
+    let on_face = manifold(Collider2D::Box(&ground()), at(0.0, 0.0, 0.0), Collider2D::Circle(&circle), at(1.0, 0.9, 0.0));
+    assert_points(&on_face, Vector::Y, &[(1.0, 0.45, 0.1)]);
+    //past the corner of the ground the normal points from the corner to the centre
+    let diagonal = (0.5 as Scalar).sqrt();
+    let on_corner = manifold(Collider2D::Box(&ground()), at(0.0, 0.0, 0.0), Collider2D::Circle(&circle), at(5.0 + 0.4*diagonal, 0.5 + 0.4*diagonal, 0.0));
+    assert_points(&on_corner, Vector::splat(diagonal), &[(5.0 - 0.05*diagonal, 0.5 - 0.05*diagonal, 0.1)]);
+    assert!(collide_colliders(Collider2D::Box(&ground()), at(0.0, 0.0, 0.0), Collider2D::Circle(&circle), at(5.4, 0.9, 0.0)).is_empty());
+
+    //capsules lying on their side rest on both ends of their core
+    let capsule = CapsuleCollider2D{center_position: Vector::ZERO, half_height: 1.0, radius: 0.25, restitution: 0.0, friction: 0.0};
+    let lying = manifold(Collider2D::Box(&ground()), at(0.0, 0.0, 0.0), Collider2D::Capsule(&capsule), at(0.0, 0.7, PI/2.0));
+    assert_points(&lying, Vector::Y, &[(-1.0, 0.475, 0.05), (1.0, 0.475, 0.05)]);
+    let standing = manifold(Collider2D::Box(&ground()), at(0.0, 0.0, 0.0), Collider2D::Capsule(&capsule), at(0.0, 1.7, 0.0));
+    assert_points(&standing, Vector::Y, &[(0.0, 0.475, 0.05)]);
+}
+
+#[test]
+fn polylines_report_the_segment_they_touch(){
+    let valley = PolylineCollider2D{vertices: vec![Vector::new(-2.0, 1.0), Vector::ZERO, Vector::new(2.0, 1.0)], restitution: 0.0, friction: 0.0};
+    let circle = CircleCollider2D{center_position: Vector::ZERO, radius: 0.5, restitution: 0.0, friction: 0.0};
+    let manifolds = collide_colliders(Collider2D::Polyline(&valley), at(0.0, 0.0, 0.0), Collider2D::Circle(&circle), at(0.0, 0.5, 0.0));
+    assert_eq!(manifolds.iter().map(|(part, _)| *part).collect::<Vec<u32>>(), vec![0, 1]);
+    //both slopes push the circle up and away from them
+    let depth = 0.5 - 0.5*2.0/(5.0 as Scalar).sqrt();
+    for ((_, manifold), normal) in manifolds.iter().zip([Vector::new(1.0, 2.0), Vector::new(-1.0, 2.0)]){
+        assert_points(manifold, normal.normalize(), &[(manifold.points[0].point.x, manifold.points[0].point.y, depth)]);
+    }
+    let resting = collide_colliders(Collider2D::Polyline(&valley), at(0.0, 0.0, 0.0), Collider2D::Circle(&circle), at(1.0, 1.0, 0.0));
+    assert_eq!(resting.iter().map(|(part, _)| *part).collect::<Vec<u32>>(), vec![1]);
+}
+
+#[test]
+fn polygon_mass_matches_the_equivalent_box(){
+    let polygon = polygon(&[(-1.0, -0.5), (1.0, -0.5), (1.0, 0.5), (-1.0, 0.5)]);
+    let box_collider = BoxCollider2D{half_extents: Vector::new(1.0, 0.5), ..ground()};
+    let (polygon_mass, box_mass) = (polygon.mass_properties(2.0), box_collider.mass_properties(2.0));
+    assert!((polygon_mass.mass - box_mass.mass).abs() < TOLERANCE, "{polygon_mass:?} {box_mass:?}");
+    assert!((polygon_mass.inertia - box_mass.inertia).abs() < TOLERANCE, "{polygon_mass:?} {box_mass:?}");
+    assert!((polygon_mass.center - box_mass.center).length() < TOLERANCE, "{polygon_mass:?} {box_mass:?}");
+}
+
+//Square ground with its top at y = 5, listed so that its top face is edge <top_edge>
+fn square_ground(top_edge: usize) -> ConvexShape2D{
+    let mut corners = vec![Vector::new(-5.0, -5.0), Vector::new(5.0, -5.0), Vector::new(5.0, 5.0), Vector::new(-5.0, 5.0)];
+    corners.rotate_left((4 + 2 - top_edge) % 4);
+    ConvexShape2D::new(corners, 0.0)
+}
+
+//Regular 20-gon of unit radius sunk 0.1 into the top of square_ground, resting on edge <bottom_edge>
+fn resting_twenty_gon(bottom_edge: usize) -> ConvexShape2D{
+    let step = 2.0*PI/20.0;
+    //edge k faces outwards halfway between its vertices, so this turns edge <bottom_edge> to face down
+    let offset = 1.5*PI - step*(bottom_edge as Scalar + 0.5);
+    let height = 5.0 + (step/2.0).cos() - 0.1;
+    ConvexShape2D::new((0..20).map(|k| Vector::new(0.0, height) + Vector::from_angle(offset + step*k as Scalar)).collect(), 0.0)
+}
+
+#[test]
+fn feature_ids_tell_apart_edges_past_the_sixteenth(){
+    //resting edge 16 on ground edge 2, and edge 0 on ground edge 3
+    let high = collide_convex(&square_ground(2), &resting_twenty_gon(16)).unwrap();
+    let low = collide_convex(&square_ground(3), &resting_twenty_gon(0)).unwrap();
+    for manifold in [&high, &low]{
+        assert!((manifold.normal - Vector::Y).length() < TOLERANCE, "{manifold:?}");
+        assert_eq!(manifold.points.len(), 2, "{manifold:?}");
+    }
+    for point in high.points.iter(){
+        assert!(low.points.iter().all(|other| other.id != point.id), "{high:?} {low:?}");
+    }
+}
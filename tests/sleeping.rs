@@ -0,0 +1,79 @@
+use bevy::prelude::*;
+use bevy_flatland::collision::*;
+use bevy_flatland::components::*;
+use bevy_flatland::scalar::*;
+use bevy_flatland::simulation::*;
+mod common;
+use common::*;
+
+//Frictionless ground, so boxes pushed along it keep their speed
+fn world_with_ground() -> App{
+    let mut app = world();
+    spawn_ground(&mut app, 20.0, 0.0, 0.0);
+    app
+}
+
+fn spawn_sliding_box(app: &mut App, x: f32, y: f32, velocity: Vector) -> Entity{
+    let body = spawn_box(app, x, y, unit_box(0.0, 0.0));
+    app.world.get_mut::<SimulationData>(body).unwrap().state.velocity = velocity;
+    body
+}
+
+fn asleep(app: &App, entity: Entity) -> bool{
+    app.world.get::<Sleeping>(entity).is_some()
+}
+
+//Steps until every one of <bodies> sleeps, failing after <steps>
+fn step_until_asleep(app: &mut App, bodies: &[Entity], steps: usize){
+    for _ in 0..steps{
+        step(app);
+        if bodies.iter().all(|body| asleep(app, *body)) {
+            return;
+        }
+    }
+    panic!("{bodies:?} never fell asleep");
+}
+
+#[test]
+fn resting_bodies_fall_asleep_and_wake_on_impulses(){
+    let mut app = world_with_ground();
+    let stack: Vec<Entity> = (0..2).map(|level| spawn_sliding_box(&mut app, 0.0, 1.0 + level as f32, Vector::ZERO)).collect();
+    step_until_asleep(&mut app, &stack, 120);
+    //the stack fell asleep together, after resting for SleepConfig::time_to_sleep
+    assert_eq!(app.world.resource::<SimulationIslands>().island(stack[0]), Some(stack.as_slice()));
+    let resting: Vec<RigidBodyState2D> = stack.iter().map(|body| state(&app, *body)).collect();
+    for _ in 0..60{
+        step(&mut app);
+    }
+    for (body, resting) in stack.iter().zip(&resting){
+        assert!(asleep(&app, *body));
+        assert_eq!(state(&app, *body), *resting);
+    }
+    //a push on the top box wakes the whole island
+    app.world.get_mut::<SimulationData>(stack[1]).unwrap().linear_impulse = Vector::new(2.0, 0.0);
+    step(&mut app);
+    assert!(stack.iter().all(|body| !asleep(&app, *body)));
+    assert!(state(&app, stack[1]).velocity.x > 1.0, "{:?}", state(&app, stack[1]));
+}
+
+#[test]
+fn awake_bodies_wake_sleeping_ones_before_hitting_them(){
+    let mut app = world_with_ground();
+    let sleeper = spawn_sliding_box(&mut app, 0.0, 1.0, Vector::ZERO);
+    step_until_asleep(&mut app, &[sleeper], 120);
+    //slides into the side of the sleeping box
+    let striker = spawn_sliding_box(&mut app, -3.0, 1.0, Vector::new(3.0, 0.0));
+    for _ in 0..120{
+        step(&mut app);
+        let touching = app.world.resource::<Contacts2D>().contacts.iter()
+            .any(|contact| [contact.body_a, contact.body_b] == [Some(sleeper), Some(striker)] || [contact.body_a, contact.body_b] == [Some(striker), Some(sleeper)]);
+        if touching {
+            //the sleeping box is woken and pushed in the step of the first contact, rather than stopping the striker like a wall
+            assert!(!asleep(&app, sleeper));
+            assert!(state(&app, sleeper).velocity.x > 1.0, "{:?}", state(&app, sleeper));
+            assert!(state(&app, striker).velocity.x > 1.0, "{:?}", state(&app, striker));
+            return;
+        }
+    }
+    panic!("the boxes never touched");
+}
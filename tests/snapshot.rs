@@ -0,0 +1,94 @@
+use std::time::Duration;
+use bevy::{prelude::*, ecs::{event::ManualEventReader, query::Has}};
+use bevy_flatland::collision::*;
+use bevy_flatland::components::*;
+use bevy_flatland::scalar::*;
+use bevy_flatland::simulation::*;
+mod common;
+use common::*;
+
+fn spawn_reporting_box(app: &mut App, x: f32, y: f32, restitution: Scalar) -> Entity{
+    let body = spawn_box(app, x, y, unit_box(restitution, 0.6));
+    app.world.entity_mut(body).insert(CollidingEntities::default());
+    body
+}
+
+//A stack settling into sleep, a box bouncing in and out of contact and a pendulum on a joint
+fn scene() -> (App, Entity){
+    let mut app = world();
+    let ground = spawn_ground(&mut app, 20.0, 0.0, 0.6);
+    app.world.entity_mut(ground).insert(CollidingEntities::default());
+    for level in 0..3{
+        spawn_reporting_box(&mut app, 0.0, 1.0 + level as f32, 0.0);
+    }
+    let bouncing = spawn_reporting_box(&mut app, 6.0, 4.0, 0.7);
+    let pendulum = spawn_body(&mut app, -8.0, 6.0);
+    app.world.spawn(Joint2D::new(JointKind2D::Revolute{limits: None, motor: None}, pendulum, Vector::new(-2.0, 0.0), None, Vector::new(-10.0, 6.0)));
+    (app, bouncing)
+}
+
+#[derive(Default)]
+struct EventReaders{
+    started: ManualEventReader<CollisionStarted>,
+    ended: ManualEventReader<CollisionEnded>
+}
+
+#[derive(Debug, PartialEq)]
+struct StepRecord{
+    bodies: Vec<(Entity, SimulationData, bool)>,
+    colliding: Vec<(Entity, Vec<Entity>)>,
+    started: Vec<(Entity, Entity)>,
+    ended: Vec<(Entity, Entity)>
+}
+
+//Everything a replay has to reproduce, in entity order
+fn record(app: &mut App, readers: &mut EventReaders) -> StepRecord{
+    let mut bodies: Vec<_> = app.world.query::<(Entity, &SimulationData, Has<Sleeping>)>().iter(&app.world)
+        .map(|(entity, data, sleeping)| (entity, data.clone(), sleeping))
+        .collect();
+    bodies.sort_unstable_by_key(|(entity, _, _)| *entity);
+    let mut colliding: Vec<_> = app.world.query::<(Entity, &CollidingEntities)>().iter(&app.world)
+        .map(|(entity, colliding)| {
+            let mut others: Vec<Entity> = colliding.0.iter().copied().collect();
+            others.sort_unstable();
+            (entity, others)
+        })
+        .collect();
+    colliding.sort_unstable_by_key(|(entity, _)| *entity);
+    let started = readers.started.iter(app.world.resource::<Events<CollisionStarted>>()).map(|event| (event.a, event.b)).collect();
+    let ended = readers.ended.iter(app.world.resource::<Events<CollisionEnded>>()).map(|event| (event.a, event.b)).collect();
+    StepRecord{bodies, colliding, started, ended}
+}
+
+#[test]
+fn restored_snapshots_replay_bit_for_bit(){
+    let (mut app, bouncing) = scene();
+    let mut readers = EventReaders::default();
+    //capture while the bouncing box touches the ground, which it won't at the end
+    for step_index in 0..{
+        step(&mut app);
+        if record(&mut app, &mut readers).started.iter().any(|(a, b)| *a == bouncing || *b == bouncing) {
+            break;
+        }
+        assert!(step_index < 120, "the bouncing box never landed");
+    }
+    app.world.resource_mut::<FixedTime>().tick(Duration::from_millis(7));
+    let snapshot = FlatlandSnapshot::capture(&mut app.world);
+
+    //long enough for the bouncing box to leave the ground again and for the stack to fall asleep
+    let original: Vec<StepRecord> = (0..150).map(|_| {
+        step(&mut app);
+        record(&mut app, &mut readers)
+    }).collect();
+    assert!(original.iter().any(|record| !record.ended.is_empty()), "nothing stopped touching");
+    assert_ne!(app.world.resource::<TouchingPairs2D>().0, snapshot.touching.0);
+    assert!(original.last().unwrap().bodies.iter().any(|(_, _, sleeping)| *sleeping), "nothing fell asleep");
+    app.world.resource_mut::<FixedTime>().tick(Duration::from_millis(5));
+
+    assert!(snapshot.restore(&mut app.world).is_empty());
+    assert_eq!(app.world.resource::<FixedTime>().accumulated(), Duration::from_millis(7));
+    for (index, expected) in original.iter().enumerate(){
+        step(&mut app);
+        assert_eq!(&record(&mut app, &mut readers), expected, "replay diverged {} steps after the restore", index + 1);
+    }
+}
@@ -0,0 +1,77 @@
+use bevy_flatland::scalar::*;
+use bevy_flatland::simulation::*;
+
+fn load(ron: &str) -> Result<SolverConfigAsset, bevy::asset::Error>{
+    SolverConfigAsset::from_ron(ron.as_bytes())
+}
+
+//Writes a config back out as a Tableau, which should load into the same coefficients
+fn tableau_ron(config: &DiffEqSolverConfig) -> String{
+    let embedded = config.embedded_weights()
+        .map(|weights| format!(", embedded_weights: Some({weights:?}), error_order: Some({})", config.embedded_accuracy_order().unwrap().min(config.accuracy_order())))
+        .unwrap_or_default();
+    format!(
+        "(method: Tableau(nodes: {:?}, weights: {:?}, coeff_matrix: {:?}, accuracy_order: {}{embedded}))",
+        config.nodes(), config.weights(), config.coeff_matrix(), config.accuracy_order()
+    )
+}
+
+#[test]
+fn presets_round_trip_through_tableau_files(){
+    for name in DiffEqSolverConfig::PRESETS{
+        let preset = DiffEqSolverConfig::preset(name).unwrap();
+        let loaded = load(&tableau_ron(&preset)).unwrap_or_else(|error| panic!("{name}: {error}")).config;
+        assert_eq!(loaded.nodes(), preset.nodes(), "{name}");
+        assert_eq!(loaded.weights(), preset.weights(), "{name}");
+        assert_eq!(loaded.coeff_matrix(), preset.coeff_matrix(), "{name}");
+        assert_eq!(loaded.embedded_weights(), preset.embedded_weights(), "{name}");
+        assert_eq!(loaded.is_implicit(), preset.is_implicit(), "{name}");
+    }
+}
+
+#[test]
+fn loads_presets_families_and_adaptive_settings(){
+    let asset = load(r#"(method: Preset("dormand_prince"), adaptive: Some((absolute_tolerance: 1e-6, max_substeps: 8)))"#).unwrap();
+    assert_eq!(asset.config.weights(), DiffEqSolverConfig::dormand_prince().weights());
+    let adaptive = asset.adaptive.unwrap();
+    assert_eq!(adaptive.absolute_tolerance, 1e-6);
+    assert_eq!(adaptive.max_substeps, 8);
+    assert_eq!(adaptive.relative_tolerance, AdaptiveStepConfig::default().relative_tolerance);
+
+    let asset = load("(method: ThirdOrder(0.5), stage_solver: Some(FixedPoint(max_iterations: 5, tolerance: 1e-3)))").unwrap();
+    assert_eq!(asset.config.weights(), DiffEqSolverConfig::third_order(0.5).weights());
+    assert!(!asset.config.is_implicit());
+    assert!(asset.adaptive.is_none());
+    assert_eq!(load("(method: SecondOrder(1.0))").unwrap().config.weights(), DiffEqSolverConfig::heun().weights());
+}
+
+#[test]
+fn rejects_invalid_files(){
+    let error = |ron: &str| load(ron).err().unwrap_or_else(|| panic!("{ron} loaded"));
+    assert!(error(r#"(method: Preset("rk5"))"#).downcast_ref::<UnknownPresetError>().is_some());
+    assert!(error("(method: ThirdOrder(1.0))").downcast_ref::<TableauError>().is_some());
+    assert!(error("(method: Tableau(nodes: [0.0], weights: [1.0]))").downcast_ref::<ron::error::SpannedError>().is_some());
+    assert!(matches!(
+        error("(method: Tableau(nodes: [0.0, 1.0], weights: [0.5, 0.5], coeff_matrix: [[0.0, 0.0], [0.5, 0.0]], accuracy_order: 2))").downcast_ref(),
+        Some(TableauError::RowSumMismatch{stage: 1, ..})
+    ));
+    assert!(matches!(
+        error("(method: Tableau(nodes: [0.0, 1.0], weights: [0.5, 0.5], coeff_matrix: [[0.0, 0.0], [1.0, 0.0]], accuracy_order: 3))").downcast_ref(),
+        Some(TableauError::OrderConditions{field: "weights", declared: 3, satisfied: 2})
+    ));
+    let heun_euler = "nodes: [0.0, 1.0], weights: [0.5, 0.5], coeff_matrix: [[0.0, 0.0], [1.0, 0.0]], accuracy_order: 2, embedded_weights: Some([1.0, 0.0])";
+    assert_eq!(
+        error(&format!("(method: Tableau({heun_euler}))")).downcast_ref::<IncompleteEmbeddedPairError>().unwrap().missing,
+        "error_order"
+    );
+    assert!(matches!(
+        error(&format!("(method: Tableau({heun_euler}, error_order: Some(0)))")).downcast_ref(),
+        Some(TableauError::InvalidParameter{name: "error_order", ..})
+    ));
+    assert!(matches!(
+        error(&format!("(method: Tableau({heun_euler}, error_order: Some(2)))")).downcast_ref(),
+        Some(TableauError::OrderConditions{field: "embedded_weights", declared: 2, satisfied: 1})
+    ));
+    let config = load(&format!("(method: Tableau({heun_euler}, error_order: Some(1)))")).unwrap().config;
+    assert_eq!(config.embedded_weights(), Some([1.0 as Scalar, 0.0].as_slice()));
+}
@@ -0,0 +1,157 @@
+use bevy::{prelude::*, ecs::system::SystemState};
+use bevy_flatland::collision::*;
+use bevy_flatland::components::*;
+use bevy_flatland::scalar::*;
+mod common;
+use common::*;
+
+const TOLERANCE: Scalar = 1e-3;
+
+struct Scene{
+    app: App,
+    ground: Entity,
+    ball: Entity,
+    slope: Entity,
+    sensor: Entity
+}
+
+//Static ground, a ball floating above it, a slope off to the left and a sensor in layer 2
+fn scene() -> Scene{
+    let mut app = world();
+    let spawn = |app: &mut App, x: f32, y: f32| app.world.spawn((Transform::from_xyz(x, y, 0.0), GlobalTransform::from_xyz(x, y, 0.0))).id();
+    let ground = spawn(&mut app, 0.0, 0.0);
+    app.world.entity_mut(ground).insert(BoxCollider2D{center_position: Vector::ZERO, half_extents: Vector::new(10.0, 0.5), restitution: 0.0, friction: 0.5});
+    let ball = spawn(&mut app, 3.0, 4.0);
+    app.world.entity_mut(ball).insert(CircleCollider2D{center_position: Vector::ZERO, radius: 1.0, restitution: 0.0, friction: 0.5});
+    let slope = spawn(&mut app, 0.0, 0.0);
+    app.world.entity_mut(slope).insert(PolylineCollider2D{vertices: vec![Vector::new(-10.0, 5.0), Vector::new(-5.0, 10.0)], restitution: 0.0, friction: 0.5});
+    let sensor = spawn(&mut app, -3.0, 3.0);
+    app.world.entity_mut(sensor).insert((
+        BoxCollider2D{center_position: Vector::ZERO, half_extents: Vector::splat(0.5), restitution: 0.0, friction: 0.0},
+        Sensor2D,
+        CollisionLayers2D::new(2, u32::MAX)
+    ));
+    //queries see the colliders once a step has placed them
+    step(&mut app);
+    Scene{app, ground, ball, slope, sensor}
+}
+
+fn assert_hit(hit: Option<SpatialHit2D>, entity: Entity, point: Vector, normal: Vector, distance: Scalar){
+    let hit = hit.unwrap_or_else(|| panic!("missed {entity:?}"));
+    assert_eq!(hit.entity, entity, "{hit:?}");
+    assert!((hit.point - point).length() < TOLERANCE, "{hit:?}, expected the point {point}");
+    assert!((hit.normal - normal).length() < TOLERANCE, "{hit:?}, expected the normal {normal}");
+    assert!((hit.distance - distance).abs() < TOLERANCE, "{hit:?}, expected the distance {distance}");
+}
+
+#[test]
+fn rays_hit_the_first_collider_along_them(){
+    let mut scene = scene();
+    let mut state: SystemState<SpatialQuery2D> = SystemState::new(&mut scene.app.world);
+    let query = state.get(&scene.app.world);
+    let all = SpatialQueryFilter2D::default();
+    let down = Vector::NEG_Y;
+    assert_hit(query.cast_ray(Vector::new(3.0, 10.0), down, 100.0, &all), scene.ball, Vector::new(3.0, 5.0), Vector::Y, 5.0);
+    //off centre, the ray meets the ball at a slant
+    let height = 4.0 + (0.75 as Scalar).sqrt();
+    assert_hit(query.cast_ray(Vector::new(3.5, 10.0), down, 100.0, &all), scene.ball, Vector::new(3.5, height), Vector::new(0.5, height - 4.0), 10.0 - height);
+    assert_hit(
+        query.cast_ray(Vector::new(3.0, 10.0), down, 100.0, &SpatialQueryFilter2D::excluding([scene.ball])),
+        scene.ground, Vector::new(3.0, 0.5), Vector::Y, 9.5
+    );
+    assert_hit(
+        query.cast_ray(Vector::new(-5.0, 5.0), Vector::new(-1.0, 1.0), 100.0, &all),
+        scene.slope, Vector::new(-7.5, 7.5), Vector::new(1.0, -1.0).normalize(), (12.5 as Scalar).sqrt()
+    );
+    assert!(query.cast_ray(Vector::new(3.0, 10.0), down, 2.0, &all).is_none());
+    assert!(query.cast_ray(Vector::new(3.0, 10.0), Vector::ZERO, 100.0, &all).is_none());
+    //starting inside a collider hits it straight away
+    let inside = query.cast_ray(Vector::ZERO, Vector::X, 2.0, &all).unwrap();
+    assert_eq!((inside.entity, inside.distance), (scene.ground, 0.0));
+}
+
+#[test]
+fn filters_skip_layers_sensors_and_excluded_colliders(){
+    let mut scene = scene();
+    let mut state: SystemState<SpatialQuery2D> = SystemState::new(&mut scene.app.world);
+    let query = state.get(&scene.app.world);
+    let ray = |filter: &SpatialQueryFilter2D| query.cast_ray(Vector::new(-3.0, 10.0), Vector::NEG_Y, 100.0, filter).map(|hit| hit.entity);
+    assert_eq!(ray(&SpatialQueryFilter2D::default()), Some(scene.sensor));
+    assert_eq!(ray(&SpatialQueryFilter2D::default().without_sensors()), Some(scene.ground));
+    assert_eq!(ray(&SpatialQueryFilter2D::default().with_filters(1)), Some(scene.ground));
+    assert_eq!(ray(&SpatialQueryFilter2D::default().with_filters(2)), Some(scene.sensor));
+    assert_eq!(ray(&SpatialQueryFilter2D::excluding([scene.sensor, scene.ground])), None);
+}
+
+#[test]
+fn shapes_cast_onto_the_first_collider_they_meet(){
+    let mut scene = scene();
+    let mut state: SystemState<SpatialQuery2D> = SystemState::new(&mut scene.app.world);
+    let query = state.get(&scene.app.world);
+    let all = SpatialQueryFilter2D::default();
+    let unit_box = BoxCollider2D{center_position: Vector::ZERO, half_extents: Vector::splat(0.5), restitution: 0.0, friction: 0.0};
+    //the flat bottom of the box lands on the top of the ball
+    assert_hit(
+        query.cast_shape(Collider2D::Box(&unit_box), Isometry2D::new(Vector::new(3.2, 10.0), 0.0), Vector::NEG_Y, 100.0, &all),
+        scene.ball, Vector::new(3.0, 5.0), Vector::Y, 4.5
+    );
+    //past the ball and the sensor, the box falls flat onto the ground, touching it anywhere along its bottom face
+    let landed = query.cast_shape(Collider2D::Box(&unit_box), Isometry2D::new(Vector::new(7.0, 10.0), 0.0), Vector::NEG_Y, 100.0, &all).unwrap();
+    assert_hit(Some(landed), scene.ground, Vector::new(landed.point.x, 0.5), Vector::Y, 9.0);
+    assert!((6.5 - TOLERANCE..=7.5 + TOLERANCE).contains(&landed.point.x), "{landed:?}");
+    assert!(query.cast_shape(Collider2D::Box(&unit_box), Isometry2D::new(Vector::new(-3.0, 10.0), 0.0), Vector::X, 100.0, &all).is_none());
+    assert!(query.cast_shape(Collider2D::Box(&unit_box), Isometry2D::new(Vector::new(3.0, 10.0), 0.0), Vector::NEG_Y, 4.0, &all).is_none());
+}
+
+#[test]
+fn points_project_onto_the_nearest_surface(){
+    let mut scene = scene();
+    let mut state: SystemState<SpatialQuery2D> = SystemState::new(&mut scene.app.world);
+    let query = state.get(&scene.app.world);
+    let all = SpatialQueryFilter2D::default();
+    let outward = Vector::new(2.0, -1.0).normalize();
+    assert_hit(query.project_point(Vector::new(5.0, 3.0), &all), scene.ball, Vector::new(3.0, 4.0) + outward, outward, (5.0 as Scalar).sqrt() - 1.0);
+    //inside, the distance to the nearest face is negative
+    assert_hit(query.project_point(Vector::new(9.8, 0.1), &all), scene.ground, Vector::new(10.0, 0.1), Vector::X, -0.2);
+    assert_eq!(query.project_point(Vector::new(5.0, 3.0), &SpatialQueryFilter2D::excluding([scene.ball])).unwrap().entity, scene.ground);
+}
+
+#[test]
+fn aabb_queries_return_every_overlapping_collider(){
+    let mut scene = scene();
+    let mut state: SystemState<SpatialQuery2D> = SystemState::new(&mut scene.app.world);
+    let query = state.get(&scene.app.world);
+    let all = SpatialQueryFilter2D::default();
+    let sorted = |mut entities: Vec<Entity>| {
+        entities.sort_unstable();
+        entities
+    };
+    assert_eq!(query.aabb_intersections(Aabb2D::new(Vector::new(2.0, 2.0), Vector::new(4.0, 4.0)), &all), vec![scene.ball]);
+    assert_eq!(
+        sorted(query.aabb_intersections(Aabb2D::new(Vector::new(-4.0, 0.0), Vector::new(4.0, 3.5)), &all)),
+        sorted(vec![scene.ground, scene.ball, scene.sensor])
+    );
+    assert_eq!(query.aabb_intersections(Aabb2D::new(Vector::new(-4.0, 0.0), Vector::new(4.0, 3.5)), &all.clone().without_sensors()).len(), 2);
+    assert!(query.aabb_intersections(Aabb2D::new(Vector::new(20.0, 20.0), Vector::new(21.0, 21.0)), &all).is_empty());
+}
+
+#[test]
+fn queries_see_bodies_where_the_solver_left_them(){
+    let mut app = world();
+    let ground = spawn_ground(&mut app, 10.0, 0.0, 0.5);
+    //sunk 0.3 into the ground, so the solver pushes it out during the step
+    let sunk = spawn_box(&mut app, 0.0, 0.7, unit_box(0.0, 0.5));
+    step(&mut app);
+    let after = state(&app, sunk);
+    assert!(after.position.y > 0.8, "{after:?}");
+    let mut query_state: SystemState<SpatialQuery2D> = SystemState::new(&mut app.world);
+    let query = query_state.get(&app.world);
+    let all = SpatialQueryFilter2D::default();
+    //the push out tips the box slightly, so the ray down its centre meets the top face a little above its half height
+    let top = after.position.y + 0.5/after.angle.cos();
+    let normal = Vector::from_angle(after.angle).rotate(Vector::Y);
+    assert_hit(query.cast_ray(Vector::new(0.0, 10.0), Vector::NEG_Y, 100.0, &all), sunk, Vector::new(0.0, top), normal, 10.0 - top);
+    //the bounds moved along, leaving only the ground where the bottom of the box was sunk
+    let vacated = Aabb2D::new(Vector::new(-0.1, 0.25), Vector::new(0.1, 0.3));
+    assert_eq!(query.aabb_intersections(vacated, &all), vec![ground]);
+}
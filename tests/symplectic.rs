@@ -0,0 +1,83 @@
+use bevy_flatland::components::*;
+use bevy_flatland::scalar::*;
+use bevy_flatland::simulation::*;
+mod common;
+use common::*;
+
+const STEPS: usize = 10_000;
+const DT: Scalar = 0.1;
+
+//Unit harmonic oscillator in x, whose energy is 1/2 from the initial state
+fn oscillator(_: Scalar, states: &[RigidBodyState2D], out: &mut [RigidBodyState2D]){
+    out[0] = RigidBodyState2D{
+        position: states[0].velocity,
+        velocity: -states[0].position,
+        ..Default::default()
+    };
+}
+
+fn energy(state: &RigidBodyState2D) -> Scalar{
+    0.5*(state.position.length_squared() + state.velocity.length_squared())
+}
+
+//Largest relative energy error over a thousand periods and a half, and the relative error at the end
+fn energy_errors(mut step: impl FnMut(Scalar, &mut [RigidBodyState2D], &mut SolverWorkspace)) -> (Scalar, Scalar){
+    let mut states = [RigidBodyState2D{position: Vector::new(1.0, 0.0), ..Default::default()}];
+    let mut workspace = SolverWorkspace::default();
+    let mut largest: Scalar = 0.0;
+    for index in 0..STEPS{
+        step(index as Scalar*DT, &mut states, &mut workspace);
+        largest = largest.max((energy(&states[0]) - 0.5).abs()/0.5);
+    }
+    (largest, (energy(&states[0]) - 0.5).abs()/0.5)
+}
+
+#[test]
+fn splitting_methods_keep_the_energy_bounded(){
+    //velocity Verlet's energy error oscillates with an amplitude of about (dt*omega)^2/8
+    for (name, config, bound) in [("velocity_verlet", SymplecticSolverConfig::velocity_verlet(), 5e-3), ("forest_ruth", SymplecticSolverConfig::forest_ruth(), 1e-4)]{
+        let (largest, _) = energy_errors(|t, states, workspace| config.step(t, DT, states, workspace, oscillator));
+        assert!(largest < bound, "{name} strayed {largest} from the initial energy");
+    }
+    //Runge-Kutta methods of the same order drift further with every step instead
+    let heun = DiffEqSolverConfig::heun();
+    let (_, drift) = energy_errors(|t, states, workspace| {
+        heun.step(t, DT, states, workspace, oscillator);
+    });
+    assert!(drift > 0.2, "heun drifted {drift}");
+    let euler = DiffEqSolverConfig::euler();
+    let (_, drift) = energy_errors(|t, states, workspace| {
+        euler.step(t, DT, states, workspace, oscillator);
+    });
+    assert!(drift > 100.0, "euler drifted {drift}");
+}
+
+//Runs a body on a stiff spring to a world anchor through the plugin, returning the largest relative energy error
+fn spring_energy_error(mode: SolverMode, config: DiffEqSolverConfig) -> Scalar{
+    let mut app = world();
+    app.insert_resource(UniformGravity2D{acceleration: Vector::ZERO});
+    app.insert_resource(mode);
+    app.insert_resource(config);
+    let body = spawn_body(&mut app, 1.0, 0.0);
+    let stiffness = 100.0;
+    app.world.spawn(DampedSpring2D{
+        body_a: body, local_anchor_a: Vector::ZERO, body_b: None, anchor_b: Vector::ZERO, rest_length: 0.0, stiffness, damping: 0.0
+    });
+    let mass = app.world.get::<RigidBody2D>(body).unwrap().mass;
+    let energy = |state: RigidBodyState2D| 0.5*mass*state.velocity.length_squared() + 0.5*stiffness*state.position.length_squared();
+    let initial = energy(state(&app, body));
+    let mut largest: Scalar = 0.0;
+    for _ in 0..3000{
+        step(&mut app);
+        largest = largest.max((energy(state(&app, body)) - initial).abs()/initial);
+    }
+    largest
+}
+
+#[test]
+fn the_symplectic_solver_mode_steps_the_world_with_splitting_methods(){
+    let symplectic = spring_energy_error(SolverMode::Symplectic, DiffEqSolverConfig::default());
+    assert!(symplectic < 0.01, "{symplectic}");
+    let euler = spring_energy_error(SolverMode::RungeKutta, DiffEqSolverConfig::euler());
+    assert!(euler > 1.0, "{euler}");
+}
@@ -0,0 +1,111 @@
+use bevy_flatland::scalar::*;
+use bevy_flatland::simulation::*;
+
+//order of accuracy of every preset, and of its embedded solution
+const PRESET_ORDERS: [(&str, usize, Option<usize>); 22] = [
+    ("euler", 1, None),
+    ("midpoint", 2, None),
+    ("heun", 2, None),
+    ("ralston", 2, None),
+    ("kutta_third", 3, None),
+    ("heun_third", 3, None),
+    ("wray_third", 3, None),
+    ("ralston_third", 3, None),
+    ("ssprk3", 3, None),
+    ("rk4", 4, None),
+    ("rule3_8", 4, None),
+    ("ralston_fourth", 4, None),
+    ("bogacki_shampine", 3, Some(2)),
+    ("fehlberg", 4, Some(5)),
+    ("dormand_prince", 5, Some(4)),
+    ("cash_karp", 5, Some(4)),
+    ("backward_euler", 1, None),
+    ("implicit_midpoint", 2, None),
+    ("gauss_legendre4", 4, None),
+    ("gauss_legendre6", 6, None),
+    ("radau_iia3", 3, None),
+    ("radau_iia5", 5, None)
+];
+
+#[test]
+fn presets_satisfy_the_order_conditions_of_their_order(){
+    assert_eq!(PRESET_ORDERS.map(|(name, _, _)| name), DiffEqSolverConfig::PRESETS);
+    for (name, order, embedded_order) in PRESET_ORDERS{
+        let config = DiffEqSolverConfig::preset(name).unwrap();
+        assert_eq!(config.accuracy_order(), order, "{name}");
+        assert_eq!(config.embedded_accuracy_order(), embedded_order, "{name}");
+        assert_eq!(config.is_embedded(), embedded_order.is_some(), "{name}");
+    }
+}
+
+#[test]
+fn parameterised_families_keep_their_order(){
+    for a in [0.1, 0.5, 2.0/3.0, 1.0, 3.0]{
+        assert_eq!(DiffEqSolverConfig::try_second_order(a).unwrap().accuracy_order(), 2, "a = {a}");
+    }
+    for a in [0.1, 0.5, 0.8, 1.5, 3.0]{
+        assert_eq!(DiffEqSolverConfig::try_third_order(a).unwrap().accuracy_order(), 3, "a = {a}");
+    }
+    assert!(DiffEqSolverConfig::try_second_order(0.0).is_err());
+    for a in [0.0, 1.0, 2.0/3.0, 0.666_667, Scalar::NAN]{
+        assert!(matches!(DiffEqSolverConfig::try_third_order(a), Err(TableauError::InvalidParameter{..})), "a = {a}");
+    }
+}
+
+#[test]
+fn from_tableau_checks_the_declared_order(){
+    for (name, order, embedded_order) in PRESET_ORDERS{
+        let preset = DiffEqSolverConfig::preset(name).unwrap();
+        let rebuild = |order| match (preset.embedded_weights(), embedded_order) {
+            (Some(embedded_weights), Some(error_order)) => DiffEqSolverConfig::from_embedded_tableau(
+                preset.nodes().to_vec(), preset.weights().to_vec(), preset.coeff_matrix().to_vec(), order,
+                embedded_weights.to_vec(), error_order.min(order)
+            ),
+            _ => DiffEqSolverConfig::from_tableau(preset.nodes().to_vec(), preset.weights().to_vec(), preset.coeff_matrix().to_vec(), order)
+        };
+        let config = rebuild(order).unwrap();
+        assert_eq!(config.accuracy_order(), order, "{name}");
+        assert_eq!(config.is_implicit(), preset.is_implicit(), "{name}");
+        assert_eq!(
+            rebuild(order + 1).unwrap_err(),
+            TableauError::OrderConditions{field: "weights", declared: order + 1, satisfied: order},
+            "{name}"
+        );
+    }
+    //Heun's third order weights on the second order tableau of Ralston's method
+    let error = DiffEqSolverConfig::from_embedded_tableau(
+        vec![0.0, 2.0/3.0], vec![0.25, 0.75], vec![vec![0.0, 0.0], vec![2.0/3.0, 0.0]], 2, vec![1.0, 0.0], 2
+    ).unwrap_err();
+    assert_eq!(error, TableauError::OrderConditions{field: "embedded_weights", declared: 2, satisfied: 1});
+}
+
+#[test]
+fn from_tableau_rejects_malformed_tableaus(){
+    let euler = || (vec![0.0], vec![1.0], vec![vec![0.0]]);
+    assert_eq!(DiffEqSolverConfig::from_tableau(vec![], vec![], vec![], 1).unwrap_err(), TableauError::Empty);
+    let (nodes, _, coeff_matrix) = euler();
+    assert_eq!(
+        DiffEqSolverConfig::from_tableau(nodes, vec![0.5, 0.5], coeff_matrix, 1).unwrap_err(),
+        TableauError::DimensionMismatch{field: "weights", expected: 1, found: 2}
+    );
+    let (nodes, weights, _) = euler();
+    assert_eq!(
+        DiffEqSolverConfig::from_tableau(nodes, weights, vec![vec![0.0, 0.0]], 1).unwrap_err(),
+        TableauError::DimensionMismatch{field: "coeff_matrix row", expected: 1, found: 2}
+    );
+    let (nodes, _, coeff_matrix) = euler();
+    assert_eq!(
+        DiffEqSolverConfig::from_tableau(nodes, vec![Scalar::INFINITY], coeff_matrix, 1).unwrap_err(),
+        TableauError::NonFinite{field: "weights"}
+    );
+    let (_, weights, coeff_matrix) = euler();
+    assert!(matches!(
+        DiffEqSolverConfig::from_tableau(vec![0.5], weights, coeff_matrix, 1).unwrap_err(),
+        TableauError::RowSumMismatch{stage: 0, ..}
+    ));
+    let (nodes, _, coeff_matrix) = euler();
+    assert!(matches!(
+        DiffEqSolverConfig::from_tableau(nodes, vec![0.9], coeff_matrix, 1).unwrap_err(),
+        TableauError::WeightSum{field: "weights", ..}
+    ));
+}
@@ -0,0 +1,64 @@
+use std::time::Duration;
+use bevy::{prelude::*, time::fixed_timestep::run_fixed_update_schedule};
+use bevy_flatland::components::*;
+use bevy_flatland::scalar::*;
+use bevy_flatland::simulation::*;
+mod common;
+use common::*;
+
+//Runs a frame <frame> long the way the app's main schedule does, returning the steps it took
+fn frame(app: &mut App, frame: Duration) -> u64{
+    let before = app.world.resource::<SimulationClock>().steps;
+    app.world.run_schedule(PreUpdate);
+    app.world.resource_mut::<FixedTime>().tick(frame);
+    run_fixed_update_schedule(&mut app.world);
+    app.world.resource::<SimulationClock>().steps - before
+}
+
+#[test]
+fn the_fixed_timestep_follows_flatland_timestep(){
+    let mut app = world();
+    app.world.resource_mut::<FlatlandTimestep>().step = 0.01;
+    app.world.run_schedule(PreUpdate);
+    assert_eq!(app.world.resource::<FixedTime>().period, Duration::from_millis(10));
+    assert_eq!(frame(&mut app, Duration::from_millis(35)), 3);
+    assert!((app.world.resource::<SimulationClock>().elapsed - 0.03).abs() < 1e-5);
+}
+
+#[test]
+fn long_frames_take_at_most_max_substeps_and_drop_the_rest(){
+    let mut app = world();
+    let body = spawn_body(&mut app, 0.0, 0.0);
+    let timestep = app.world.resource::<FlatlandTimestep>().clone();
+    let period = Duration::from_secs_f64(to_f64(timestep.step));
+    //a frame long enough for 20 steps
+    assert_eq!(frame(&mut app, period*20), timestep.max_substeps as u64);
+    assert!(app.world.resource::<FixedTime>().accumulated() < period);
+    //the dropped time is lost, so the body has fallen for max_substeps steps only
+    let simulated = timestep.max_substeps as Scalar*timestep.step;
+    assert!((state(&app, body).velocity.y + 9.81*simulated).abs() < 1e-3, "{:?}", state(&app, body));
+    //the budget is refilled every frame
+    assert_eq!(frame(&mut app, period*2), 2);
+}
+
+#[test]
+fn transforms_are_interpolated_by_the_overstep(){
+    let mut app = world();
+    let period = app.world.resource::<FixedTime>().period;
+    let previous = RigidBodyState2D{position: Vector::ZERO, angle: 0.0, ..Default::default()};
+    let current = RigidBodyState2D{position: Vector::new(4.0, 2.0), angle: 1.0, ..Default::default()};
+    let data = SimulationData{previous, state: current, ..Default::default()};
+    let point = app.world.spawn((Transform::from_xyz(0.0, 0.0, 3.0), data.clone())).id();
+    //the state follows the centre of mass, a unit to the right of the origin
+    let body = RigidBody2D{center_of_mass: Vector::new(1.0, 0.0), ..RigidBody2D::new(1.0, 1.0)};
+    let offset = app.world.spawn((Transform::default(), data, body)).id();
+    app.world.resource_mut::<FixedTime>().tick(period/4);
+    app.world.run_schedule(PostUpdate);
+
+    let transform = *app.world.get::<Transform>(point).unwrap();
+    assert!((transform.translation - Vec3::new(1.0, 0.5, 3.0)).length() < 1e-4, "{transform:?}");
+    assert!((transform.rotation.to_euler(EulerRot::ZYX).0 - 0.25).abs() < 1e-4, "{transform:?}");
+    let transform = *app.world.get::<Transform>(offset).unwrap();
+    let origin = Vec2::new(1.0, 0.5) - Vec2::from_angle(0.25).rotate(Vec2::X);
+    assert!((transform.translation.truncate() - origin).length() < 1e-4, "{transform:?}");
+}